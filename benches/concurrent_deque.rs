@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_dsa::doubly_linked_list::concurrent::ConcurrentDeque;
+
+const OPS: usize = 10_000;
+
+// every thread pushes onto one end and pops off the other, like a shared work queue
+fn concurrent_deque(threads: usize) {
+    let d = Arc::new(ConcurrentDeque::<usize>::new());
+    let handles = (0..threads)
+        .map(|_| {
+            let d = Arc::clone(&d);
+            thread::spawn(move || {
+                for n in 0..OPS / threads {
+                    d.push_back(n);
+                    d.pop_front();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+fn mutex_vec_deque(threads: usize) {
+    let d = Arc::new(Mutex::new(VecDeque::<usize>::new()));
+    let handles = (0..threads)
+        .map(|_| {
+            let d = Arc::clone(&d);
+            thread::spawn(move || {
+                for n in 0..OPS / threads {
+                    d.lock().unwrap().push_back(n);
+                    d.lock().unwrap().pop_front();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+fn concurrent_deque_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ConcurrentDeque");
+    for threads in [1, 4].iter() {
+
+        group.bench_with_input(BenchmarkId::new("ConcurrentDeque", threads), threads, |b, t| b.iter(|| concurrent_deque(*t)));
+        group.bench_with_input(BenchmarkId::new("Mutex<VecDeque>", threads), threads, |b, t| b.iter(|| mutex_vec_deque(*t)));
+    }
+}
+
+criterion_group!(benches, concurrent_deque_bench);
+criterion_main!(benches);
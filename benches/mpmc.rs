@@ -0,0 +1,63 @@
+use std::sync::mpsc;
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_dsa::ring_buffer::mpmc;
+
+const MESSAGES: usize = 10_000;
+const CAP: usize = 64;
+
+fn mpmc_channel(producers: usize, consumers: usize) {
+    let (tx, rx) = mpmc::bounded::<usize>(CAP);
+    let handles = (0..producers)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for n in 0..MESSAGES / producers {
+                    tx.send(n).unwrap();
+                }
+            })
+        })
+        .chain((0..consumers).map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || while rx.recv().is_ok() {})
+        }))
+        .collect::<Vec<_>>();
+    drop((tx, rx));
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+// std's receiver can't be cloned, so it only gets the single-consumer cases
+fn std_sync_channel(producers: usize) {
+    let (tx, rx) = mpsc::sync_channel::<usize>(CAP);
+    let handles = (0..producers)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for n in 0..MESSAGES / producers {
+                    tx.send(n).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+    while rx.recv().is_ok() {}
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+fn mpmc_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Channel");
+    for producers in [1, 4].iter() {
+
+        group.bench_with_input(BenchmarkId::new("mpmc", format!("{producers}x1")), producers, |b, p| b.iter(|| mpmc_channel(*p, 1)));
+        group.bench_with_input(BenchmarkId::new("std::sync::mpsc", format!("{producers}x1")), producers, |b, p| b.iter(|| std_sync_channel(*p)));
+    }
+    group.bench_function(BenchmarkId::new("mpmc", "4x4"), |b| b.iter(|| mpmc_channel(4, 4)));
+}
+
+criterion_group!(benches, mpmc_bench);
+criterion_main!(benches);
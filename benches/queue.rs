@@ -1,46 +1,80 @@
+use std::iter;
+use std::slice;
+
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use rust_dsa::queue::{ArrayQueue, LinkedQueue, StdArrayQueue, StdLinkedQueue};
+use rust_dsa::array_list::ArrayList;
+// every alias, for `for_each_queue!`
+use rust_dsa::queue::*;
+use rust_dsa::traits::Queue;
 
-fn array_queue(size: usize) {
-    let mut s = ArrayQueue::<usize>::new();
-    for n in 0..size {
-        s.push(n);
+/// The old `ArrayQueue`'s backend, which pushes at the front and so shifts the
+/// whole list on every push. Only here to compare against.
+struct Shifting<T>(ArrayList<T>);
+
+impl<T> QueueBackend for Shifting<T> {
+    type Item = T;
+    type Iter<'a> = iter::Rev<slice::Iter<'a, T>>
+    where
+        T: 'a;
+
+    fn with_capacity(cap: usize) -> Self {
+        Shifting(ArrayList::with_capacity(cap))
     }
-    while let Some(_) = s.pop() {}
-}
 
-fn linked_queue(size: usize) {
-    let mut s = LinkedQueue::<usize>::new();
-    for n in 0..size {
-        s.push(n);
+    fn push(&mut self, val: T) {
+        self.0.push_front(val)
     }
-    while let Some(_) = s.pop() {}
-}
 
-fn std_array_queue(size: usize) {
-    let mut s = StdArrayQueue::<usize>::new();
-    for n in 0..size {
-        s.push(n);
+    fn pop(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.0.last()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.0.last_mut()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter().rev()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear()
     }
-    while let Some(_) = s.pop() {}
 }
 
-fn std_linked_queue(size: usize) {
-    let mut s = StdLinkedQueue::<usize>::new();
+type ShiftingArrayQueue<T> = GenericQueue<Shifting<T>>;
+
+fn push_then_pop<S: Queue<usize> + Default>(size: usize) {
+    let mut s = S::default();
     for n in 0..size {
         s.push(n);
     }
-    while let Some(_) = s.pop() {}
+    while s.pop().is_some() {}
+}
+
+/// Benches `push_then_pop` on each backend, named after its type.
+macro_rules! bench_backends {
+    ($group:expr, $size:expr, [$($backend:ident),*]) => {$(
+        $group.bench_with_input(BenchmarkId::new(stringify!($backend), $size), $size, |b, size| {
+            b.iter(|| push_then_pop::<$backend<usize>>(*size))
+        });
+    )*};
 }
 
 fn queue_bench(c: &mut Criterion) {
     let mut group = c.benchmark_group("Queue");
-    for size in [25, 50, 100].iter() {
-
-        group.bench_with_input(BenchmarkId::new("ArrayQueue", size), size, |b, size| b.iter(|| array_queue(*size)));
-        group.bench_with_input(BenchmarkId::new("LinkedQueue", size), size, |b, size| b.iter(|| linked_queue(*size)));
-        group.bench_with_input(BenchmarkId::new("StdArrayQueue", size), size, |b, size| b.iter(|| std_array_queue(*size)));
-        group.bench_with_input(BenchmarkId::new("StdLinkedQueue", size), size, |b, size| b.iter(|| std_linked_queue(*size)));
+    // big enough to see where shifting stops paying off against the ring buffers
+    for size in [25, 100, 1_000, 10_000].iter() {
+        rust_dsa::for_each_queue!(bench_backends!(group, size));
+        bench_backends!(group, size, [ShiftingArrayQueue]);
     }
 }
 
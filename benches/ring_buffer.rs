@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_dsa::ring_buffer::RingBuffer;
+
+const OPS: usize = 10_000;
+
+// keeps the buffer half full so every push and pop wraps around regularly
+fn push_pop(mut rb: RingBuffer<usize>) {
+    let cap = rb.capacity();
+    for n in 0..cap / 2 {
+        rb.push_back(n).unwrap();
+    }
+    for n in 0..OPS {
+        rb.push_back(n).unwrap();
+        black_box(rb.pop_front());
+    }
+}
+
+fn ring_buffer_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("RingBuffer");
+    for cap in [64, 1024].iter() {
+
+        group.bench_with_input(BenchmarkId::new("Modulo", cap), cap, |b, cap| b.iter(|| push_pop(RingBuffer::with_capacity(*cap))));
+        group.bench_with_input(BenchmarkId::new("Masked", cap), cap, |b, cap| b.iter(|| push_pop(RingBuffer::with_capacity(*cap).with_masked_indexing())));
+    }
+}
+
+criterion_group!(benches, ring_buffer_bench);
+criterion_main!(benches);
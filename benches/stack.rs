@@ -1,47 +1,29 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use rust_dsa::stack::{ArrayStack, LinkedStack, StdArrayStack, StdLinkedStack};
+// every alias, for `for_each_stack!`
+use rust_dsa::stack::*;
+use rust_dsa::traits::Stack;
 
-fn array_stack(size: usize) {
-    let mut s = ArrayStack::<usize>::new();
+fn push_then_pop<S: Stack<usize> + Default>(size: usize) {
+    let mut s = S::default();
     for n in 0..size {
         s.push(n);
     }
-    while let Some(_) = s.pop() {}
+    while s.pop().is_some() {}
 }
 
-fn linked_stack(size: usize) {
-    let mut s = LinkedStack::<usize>::new();
-    for n in 0..size {
-        s.push(n);
-    }
-    while let Some(_) = s.pop() {}
-}
-
-fn std_array_stack(size: usize) {
-    let mut s = StdArrayStack::<usize>::new();
-    for n in 0..size {
-        s.push(n);
-    }
-    while let Some(_) = s.pop() {}
+/// Benches `push_then_pop` on each backend, named after its type.
+macro_rules! bench_backends {
+    ($group:expr, $size:expr, [$($backend:ident),*]) => {$(
+        $group.bench_with_input(BenchmarkId::new(stringify!($backend), $size), $size, |b, size| {
+            b.iter(|| push_then_pop::<$backend<usize>>(*size))
+        });
+    )*};
 }
 
-fn std_linked_stack(size: usize) {
-    let mut s = StdLinkedStack::<usize>::new();
-    for n in 0..size {
-        s.push(n);
-    }
-    while let Some(_) = s.pop() {}
-}
-
-
 fn stack_bench(c: &mut Criterion) {
     let mut group = c.benchmark_group("Stack");
     for size in [25, 50, 100].iter() {
-
-        group.bench_with_input(BenchmarkId::new("ArrayStack", size), size, |b, size| b.iter(|| array_stack(*size)));
-        group.bench_with_input(BenchmarkId::new("LinkedStack", size), size, |b, size| b.iter(|| linked_stack(*size)));
-        group.bench_with_input(BenchmarkId::new("StdArrayStack", size), size, |b, size| b.iter(|| std_array_stack(*size)));
-        group.bench_with_input(BenchmarkId::new("StdLinkedStack", size), size, |b, size| b.iter(|| std_linked_stack(*size)));
+        rust_dsa::for_each_stack!(bench_backends!(group, size));
     }
 }
 
@@ -1,22 +1,64 @@
 #![allow(dead_code)]
 
-use std::ops::{Index, IndexMut};
+use std::borrow::{Borrow, BorrowMut};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::{Bound, Deref, DerefMut, Index, IndexMut, RangeBounds};
 use std::ptr;
+use std::slice::{self, SliceIndex};
 
-use crate::rawvec::RawVec;
+use crate::rawvec::{Allocator, Doubling, Global, GrowthPolicy, RawVec, TryReserveError};
 
-pub struct ArrayList<T> {
-    buf: RawVec<T>,
+pub struct ArrayList<T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
+    buf: RawVec<T, A, G>,
     len: usize,
 }
 
 impl<T> ArrayList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+}
+
+impl<T, A: Allocator + Default, G: GrowthPolicy + Default> Default for ArrayList<T, A, G> {
+    fn default() -> Self {
+        ArrayList::new_in(A::default()).with_growth_policy(G::default())
+    }
+}
+
+impl<T, A: Allocator> ArrayList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        ArrayList {
+            buf: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
         ArrayList {
-            buf: RawVec::new(),
+            buf: RawVec::with_capacity_in(cap, alloc),
             len: 0,
         }
     }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> ArrayList<T, A, G> {
+    /// Swaps how the list grows from now on, e.g. `ArrayList::new().with_growth_policy(OneAndHalf)`.
+    pub fn with_growth_policy<P: GrowthPolicy>(self, growth: P) -> ArrayList<T, A, P> {
+        let me = mem::ManuallyDrop::new(self);
+        // `me` is never dropped, so the buffer is moved out exactly once
+        let buf = unsafe { ptr::read(&me.buf) };
+        ArrayList {
+            buf: buf.with_growth_policy(growth),
+            len: me.len,
+        }
+    }
 
     pub fn push_front(&mut self, val: T) {
         if self.len == self.buf.cap() {
@@ -57,57 +99,465 @@ impl<T> ArrayList<T> {
         }
     }
 
-    fn ptr_at_offset(&self, offset: usize) -> *mut T {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.cap()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(self.len, additional);
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.buf.reserve_exact(self.len, additional);
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve(self.len, additional)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to_fit(self.len);
+    }
+
+    /// Inserts `val` at `index`, shifting everything after it to the right.
+    pub fn insert(&mut self, index: usize, val: T) {
+        if index > self.len {
+            panic!("insertion index (is {index}) should be <= len (is {})", self.len);
+        }
+
+        if self.len == self.buf.cap() {
+            self.buf.grow();
+        }
+
         unsafe {
-            self.buf.ptr.as_ptr().add(offset)
+            let p = self.ptr_at_offset(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            ptr::write(p, val);
         }
+
+        self.len += 1;
     }
-}
 
-impl<T> Index<usize> for ArrayList<T> {
-    type Output = T;
+    /// Removes the element at `index`, shifting everything after it to the left.
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len {
+            panic!("removal index (is {index}) should be < len (is {})", self.len);
+        }
+
+        self.len -= 1;
 
-    fn index<'a>(&'a self, index: usize) -> &'a T {
+        unsafe {
+            let p = self.ptr_at_offset(index);
+            let val = ptr::read(p);
+            ptr::copy(p.add(1), p, self.len - index);
+            val
+        }
+    }
+
+    /// Removes the element at `index` in O(1) by moving the last element into its place.
+    pub fn swap_remove(&mut self, index: usize) -> T {
         if index >= self.len {
-            panic!("Index out of bounds");
+            panic!("swap_remove index (is {index}) should be < len (is {})", self.len);
         }
 
+        self.len -= 1;
+
         unsafe {
-            let p = self.buf.ptr.as_ptr().add(index);
-            &*p
+            let p = self.ptr_at_offset(index);
+            let val = ptr::read(p);
+            ptr::copy(self.ptr_at_offset(self.len), p, 1);
+            val
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let remaining = self.len - len;
+        // shorten first so a panicking drop can't lead to a double drop
+        self.len = len;
+
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(self.ptr_at_offset(len), remaining);
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Keeps only the elements for which `f` returns true, preserving their order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let original_len = self.len;
+        // the guard owns the elements until it's done, so a panic in `f`
+        // or in a drop leaves a valid list instead of double dropping
+        self.len = 0;
+
+        let mut g = BackshiftOnDrop {
+            list: self,
+            processed: 0,
+            deleted: 0,
+            original_len,
+        };
+
+        while g.processed < original_len {
+            let cur = g.list.ptr_at_offset(g.processed);
+
+            if !f(unsafe { &*cur }) {
+                g.processed += 1;
+                g.deleted += 1;
+                unsafe { ptr::drop_in_place(cur) };
+                continue;
+            }
+
+            if g.deleted > 0 {
+                unsafe {
+                    let hole = g.list.ptr_at_offset(g.processed - g.deleted);
+                    ptr::copy_nonoverlapping(cur, hole, 1);
+                }
+            }
+            g.processed += 1;
+        }
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)` returns true.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        if self.len <= 1 {
+            return;
+        }
+
+        let original_len = self.len;
+        self.len = 0;
+
+        // the first element is always kept
+        let mut g = BackshiftOnDrop {
+            list: self,
+            processed: 1,
+            deleted: 0,
+            original_len,
+        };
+
+        while g.processed < original_len {
+            unsafe {
+                let cur = g.list.ptr_at_offset(g.processed);
+                let prev = g.list.ptr_at_offset(g.processed - g.deleted - 1);
+
+                if same_bucket(&mut *cur, &mut *prev) {
+                    g.processed += 1;
+                    g.deleted += 1;
+                    ptr::drop_in_place(cur);
+                    continue;
+                }
+
+                if g.deleted > 0 {
+                    let hole = g.list.ptr_at_offset(g.processed - g.deleted);
+                    ptr::copy_nonoverlapping(cur, hole, 1);
+                }
+            }
+            g.processed += 1;
+        }
+    }
+
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Moves every element of `other` to the end of this list, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        self.reserve(other.len);
+
+        unsafe {
+            ptr::copy_nonoverlapping(other.ptr_at_offset(0), self.ptr_at_offset(self.len), other.len);
+        }
+
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// Removes `range` from the list, yielding the removed elements.
+    /// Whatever the iterator doesn't yield is dropped along with it.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A, G> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("range start overflowed"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("range end overflowed"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+
+        if start > end {
+            panic!("slice index starts at {start} but ends at {end}");
+        }
+        if end > self.len {
+            panic!("range end index {end} out of range for slice of length {}", self.len);
+        }
+
+        let tail_len = self.len - end;
+        // if the Drain is leaked the drained elements and the tail are leaked with it
+        self.len = start;
+
+        Drain {
+            list: self,
+            idx: start,
+            end,
+            tail_start: end,
+            tail_len,
+        }
+    }
+
+    fn ptr_at_offset(&self, offset: usize) -> *mut T {
+        unsafe {
+            self.buf.ptr.as_ptr().add(offset)
         }
     }
 }
 
-impl<T> IndexMut<usize> for ArrayList<T> {
-    fn index_mut<'a>(&'a mut self, index: usize) -> &'a mut Self::Output {
-        if index >= self.len {
-            panic!("Index out of bounds");
+impl<T, A: Allocator + Clone, G: GrowthPolicy + Clone> ArrayList<T, A, G> {
+    /// Splits the list in two at `at`, returning everything from `at` onwards.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if at > self.len {
+            panic!("`at` split index (is {at}) should be <= len (is {})", self.len);
         }
 
+        let other_len = self.len - at;
+        let buf = RawVec::new_in(self.buf.allocator().clone())
+            .with_growth_policy(self.buf.growth_policy().clone());
+        let mut other = ArrayList { buf, len: 0 };
+        other.reserve_exact(other_len);
+
         unsafe {
-            let p = self.buf.ptr.as_ptr().add(index);
-            &mut *p
+            ptr::copy_nonoverlapping(self.ptr_at_offset(at), other.ptr_at_offset(0), other_len);
+        }
+
+        self.len = at;
+        other.len = other_len;
+        other
+    }
+}
+
+impl<T: PartialEq, A: Allocator, G: GrowthPolicy> ArrayList<T, A, G> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+impl<T: Clone, A: Allocator, G: GrowthPolicy> ArrayList<T, A, G> {
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.reserve(other.len());
+
+        for v in other {
+            // len is bumped one at a time so a panicking clone doesn't leak
+            // or expose uninitialized slots
+            unsafe {
+                ptr::write(self.ptr_at_offset(self.len), v.clone());
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Shifts the unprocessed tail back over the removed holes, even if we're unwinding.
+struct BackshiftOnDrop<'a, T, A: Allocator, G: GrowthPolicy> {
+    list: &'a mut ArrayList<T, A, G>,
+    processed: usize,
+    deleted: usize,
+    original_len: usize,
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for BackshiftOnDrop<'_, T, A, G> {
+    fn drop(&mut self) {
+        if self.deleted > 0 {
+            unsafe {
+                let src = self.list.ptr_at_offset(self.processed);
+                let dst = self.list.ptr_at_offset(self.processed - self.deleted);
+                ptr::copy(src, dst, self.original_len - self.processed);
+            }
+        }
+
+        self.list.len = self.original_len - self.deleted;
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Deref for ArrayList<T, A, G> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.buf.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> DerefMut for ArrayList<T, A, G> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> AsRef<[T]> for ArrayList<T, A, G> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> AsMut<[T]> for ArrayList<T, A, G> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Borrow<[T]> for ArrayList<T, A, G> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> BorrowMut<[T]> for ArrayList<T, A, G> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+/// Indexing goes through the slice, so `l[2]`, `l[1..3]` and `l[..]` all work.
+impl<T, I: SliceIndex<[T]>, A: Allocator, G: GrowthPolicy> Index<I> for ArrayList<T, A, G> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        Index::index(&**self, index)
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, A: Allocator, G: GrowthPolicy> IndexMut<I> for ArrayList<T, A, G> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(&mut **self, index)
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> Clone for ArrayList<T, A, G> {
+    fn clone(&self) -> Self {
+        let buf = RawVec::with_capacity_in(self.len, self.buf.allocator().clone())
+            .with_growth_policy(self.buf.growth_policy().clone());
+        let mut l = ArrayList { buf, len: 0 };
+        l.extend_from_slice(self);
+        l
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator, G: GrowthPolicy> fmt::Debug for ArrayList<T, A, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, A: Allocator, G: GrowthPolicy> PartialEq for ArrayList<T, A, G> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, A: Allocator, G: GrowthPolicy> Eq for ArrayList<T, A, G> {}
+
+impl<T: PartialOrd, A: Allocator, G: GrowthPolicy> PartialOrd for ArrayList<T, A, G> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord, A: Allocator, G: GrowthPolicy> Ord for ArrayList<T, A, G> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: Hash, A: Allocator, G: GrowthPolicy> Hash for ArrayList<T, A, G> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Extend<T> for ArrayList<T, A, G> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for v in iter {
+            self.push(v);
         }
     }
 }
 
-impl<T> IntoIterator for ArrayList<T> {
+impl<'a, T: Copy + 'a, A: Allocator, G: GrowthPolicy> Extend<&'a T> for ArrayList<T, A, G> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T> FromIterator<T> for ArrayList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut l = ArrayList::new();
+        l.extend(iter);
+        l
+    }
+}
+
+impl<T> From<Vec<T>> for ArrayList<T> {
+    fn from(v: Vec<T>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for ArrayList<T> {
+    fn from(arr: [T; N]) -> Self {
+        arr.into_iter().collect()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IntoIterator for ArrayList<T, A, G> {
     type Item = T;
-    type IntoIter = ArrayListIter<T>;
+    type IntoIter = ArrayListIter<T, A, G>;
 
     fn into_iter(self) -> Self::IntoIter {
         ArrayListIter::new(self)
     }
 }
 
-pub struct ArrayListIter<T> {
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a ArrayList<T, A, G> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a mut ArrayList<T, A, G> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+pub struct ArrayListIter<T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
     ptr: *mut T,
-    list: ArrayList<T>,
+    list: ArrayList<T, A, G>,
 }
 
-impl<T> ArrayListIter<T> {
-    fn new(list: ArrayList<T>) -> Self {
+impl<T, A: Allocator, G: GrowthPolicy> ArrayListIter<T, A, G> {
+    fn new(list: ArrayList<T, A, G>) -> Self {
         ArrayListIter {
             ptr: list.buf.ptr.as_ptr(),
             list,
@@ -115,7 +565,7 @@ impl<T> ArrayListIter<T> {
     }
 }
 
-impl<T> Iterator for ArrayListIter<T> {
+impl<T, A: Allocator, G: GrowthPolicy> Iterator for ArrayListIter<T, A, G> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -132,17 +582,107 @@ impl<T> Iterator for ArrayListIter<T> {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
 }
 
-impl<T> Drop for ArrayListIter<T> {
+impl<T, A: Allocator, G: GrowthPolicy> DoubleEndedIterator for ArrayListIter<T, A, G> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.list.len > 0 {
+            self.list.len -= 1;
+            Some(unsafe { ptr::read(self.ptr.add(self.list.len)) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> ExactSizeIterator for ArrayListIter<T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for ArrayListIter<T, A, G> {
     fn drop(&mut self) {
-        while let Some(v) = self.next() {
+        for v in self.by_ref() {
             drop(v);
         }
     }
 }
 
-impl<T> Drop for ArrayList<T> {
+pub struct Drain<'a, T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
+    list: &'a mut ArrayList<T, A, G>,
+    idx: usize,
+    end: usize,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Iterator for Drain<'_, T, A, G> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx == self.end {
+            return None;
+        }
+
+        let v = unsafe { ptr::read(self.list.ptr_at_offset(self.idx)) };
+        self.idx += 1;
+        Some(v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.idx;
+        (n, Some(n))
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> DoubleEndedIterator for Drain<'_, T, A, G> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.idx == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(unsafe { ptr::read(self.list.ptr_at_offset(self.end)) })
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> ExactSizeIterator for Drain<'_, T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for Drain<'_, T, A, G> {
+    fn drop(&mut self) {
+        /// Moves the tail back into place even if dropping an element panics.
+        struct MoveTail<'r, 'a, T, A: Allocator, G: GrowthPolicy>(&'r mut Drain<'a, T, A, G>);
+
+        impl<T, A: Allocator, G: GrowthPolicy> Drop for MoveTail<'_, '_, T, A, G> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let start = drain.list.len;
+
+                if drain.tail_len > 0 && drain.tail_start != start {
+                    unsafe {
+                        let src = drain.list.ptr_at_offset(drain.tail_start);
+                        let dst = drain.list.ptr_at_offset(start);
+                        ptr::copy(src, dst, drain.tail_len);
+                    }
+                }
+
+                drain.list.len = start + drain.tail_len;
+            }
+        }
+
+        let guard = MoveTail(self);
+        let remaining = guard.0.end - guard.0.idx;
+        let p = guard.0.list.ptr_at_offset(guard.0.idx);
+        guard.0.idx = guard.0.end;
+
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(p, remaining));
+        }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for ArrayList<T, A, G> {
     fn drop(&mut self) {
         while let Some(v) = self.pop() {
             drop(v);
@@ -150,6 +690,7 @@ impl<T> Drop for ArrayList<T> {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -194,4 +735,244 @@ mod tests {
         assert_eq!(i.next(), Some("hey".to_string()));
     }
 
+    fn list_of(v: &[i32]) -> ArrayList<i32> {
+        let mut l = ArrayList::new();
+        l.extend_from_slice(v);
+        l
+    }
+
+    fn to_vec<T: Clone>(l: &ArrayList<T>) -> Vec<T> {
+        (0..l.len()).map(|i| l[i].clone()).collect()
+    }
+
+    #[test]
+    fn vec_api_works() {
+        let mut l = ArrayList::<i32>::with_capacity(3);
+        assert!(l.is_empty());
+        assert_eq!(l.capacity(), 3);
+        l.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(l.capacity(), 3);
+        l.insert(0, 0);
+        l.insert(4, 4);
+        l.insert(2, 9);
+        assert_eq!(to_vec(&l), vec![0, 1, 9, 2, 3, 4]);
+        assert_eq!(l.remove(2), 9);
+        assert_eq!(l.swap_remove(0), 0);
+        assert_eq!(to_vec(&l), vec![4, 1, 2, 3]);
+        l.truncate(10);
+        assert_eq!(l.len(), 4);
+        l.truncate(2);
+        assert_eq!(to_vec(&l), vec![4, 1]);
+        l.shrink_to_fit();
+        assert_eq!(l.capacity(), 2);
+        l.clear();
+        assert!(l.is_empty());
+
+        let mut l = list_of(&[1, 2, 3, 4, 5, 6]);
+        l.retain(|&v| v % 2 == 0);
+        assert_eq!(to_vec(&l), vec![2, 4, 6]);
+
+        let mut l = list_of(&[1, 1, 2, 3, 3, 3, 1, 4, 4]);
+        l.dedup();
+        assert_eq!(to_vec(&l), vec![1, 2, 3, 1, 4]);
+        l.dedup_by_key(|v| *v / 2);
+        assert_eq!(to_vec(&l), vec![1, 2, 1, 4]);
+
+        let mut l = list_of(&[1, 2, 3, 4, 5]);
+        let mut tail = l.split_off(3);
+        assert_eq!(to_vec(&l), vec![1, 2, 3]);
+        assert_eq!(to_vec(&tail), vec![4, 5]);
+        tail.append(&mut l);
+        assert!(l.is_empty());
+        assert_eq!(to_vec(&tail), vec![4, 5, 1, 2, 3]);
+        assert_eq!(l.split_off(0).len(), 0);
+    }
+
+    #[test]
+    fn slice_api_works() {
+        let mut l = list_of(&[5, 3, 8, 1, 9, 2]);
+        assert_eq!(&l[1..3], &[3, 8]);
+        assert_eq!(&l[4..], &[9, 2]);
+        assert_eq!(&l[..=1], &[5, 3]);
+        l[..2].copy_from_slice(&[7, 7]);
+        assert_eq!(l.first(), Some(&7));
+
+        l.sort();
+        assert_eq!(&l[..], &[1, 2, 7, 7, 8, 9]);
+        assert_eq!(l.binary_search(&8), Ok(4));
+        assert_eq!(l.windows(2).filter(|w| w[0] == w[1]).count(), 1);
+        assert_eq!(l.chunks(4).map(|c| c.len()).collect::<Vec<_>>(), vec![4, 2]);
+        assert_eq!(l.iter().sum::<i32>(), 34);
+        for v in l.iter_mut() {
+            *v *= 2;
+        }
+        assert_eq!(l.as_ref(), &[2, 4, 14, 14, 16, 18]);
+        assert_eq!(Borrow::<[i32]>::borrow(&l).len(), 6);
+    }
+
+    #[test]
+    fn list_iter_works() {
+        let mut l = list_of(&[1, 2, 3]);
+        let mut sum = 0;
+        for v in &l {
+            sum += v;
+        }
+        assert_eq!(sum, 6);
+        for v in &mut l {
+            *v += 1;
+        }
+        assert_eq!(l.iter().rev().copied().collect::<Vec<_>>(), vec![4, 3, 2]);
+
+        let mut it = l.into_iter();
+        assert_eq!(it.len(), 3);
+        assert_eq!(it.next_back(), Some(4));
+        assert_eq!(it.next(), Some(2));
+        assert_eq!(it.next_back(), Some(3));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds_panics() {
+        let l = list_of(&[1, 2]);
+        let _ = &l[1..3];
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds_panics() {
+        let mut l = list_of(&[1]);
+        l.insert(2, 5);
+    }
+
+    #[test]
+    fn drain_works() {
+        let mut l = list_of(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(l.drain(1..3).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(to_vec(&l), vec![1, 4, 5, 6]);
+
+        let mut d = l.drain(1..=2);
+        assert_eq!(d.len(), 2);
+        assert_eq!(d.next_back(), Some(5));
+        drop(d);
+        assert_eq!(to_vec(&l), vec![1, 6]);
+
+        let mut l = ArrayList::<String>::new();
+        l.push("a".to_string());
+        l.push("b".to_string());
+        l.push("c".to_string());
+        // dropping a partially consumed drain drops the rest of the range
+        let mut d = l.drain(..2);
+        assert_eq!(d.next(), Some("a".to_string()));
+        drop(d);
+        assert_eq!(to_vec(&l), vec!["c".to_string()]);
+        assert_eq!(l.drain(..).count(), 1);
+        assert!(l.is_empty());
+    }
+
+    #[test]
+    fn retain_and_drain_are_panic_safe() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::rc::Rc;
+
+        let marker = Rc::new(());
+        let mut l = ArrayList::new();
+        for i in 0..6 {
+            l.push((i, marker.clone()));
+        }
+
+        let r = catch_unwind(AssertUnwindSafe(|| {
+            l.retain(|(i, _)| {
+                if *i == 3 {
+                    panic!("boom");
+                }
+                i % 2 == 0
+            })
+        }));
+        assert!(r.is_err());
+        // 1 was removed, everything from 3 on was never looked at
+        assert_eq!(l.len(), 5);
+        assert_eq!(Rc::strong_count(&marker), 6);
+        assert_eq!((0..l.len()).map(|i| l[i].0).collect::<Vec<_>>(), vec![0, 2, 3, 4, 5]);
+
+        struct PanicOnDrop(bool);
+
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                if self.0 {
+                    panic!("boom");
+                }
+            }
+        }
+
+        let mut l = ArrayList::new();
+        l.push(PanicOnDrop(false));
+        l.push(PanicOnDrop(true));
+        l.push(PanicOnDrop(false));
+        l.push(PanicOnDrop(false));
+        let r = catch_unwind(AssertUnwindSafe(|| {
+            l.drain(..2);
+        }));
+        assert!(r.is_err());
+        // the tail is still moved back into place
+        assert_eq!(l.len(), 2);
+    }
+
+    #[test]
+    fn growth_policy_list_works() {
+        use crate::rawvec::FixedIncrement;
+
+        let mut l = ArrayList::<u32>::new().with_growth_policy(FixedIncrement(2));
+        l.push(1);
+        assert_eq!(l.buf.cap(), 2);
+        l.push(2);
+        l.push(3);
+        assert_eq!(l.buf.cap(), 4);
+        let mut l = l.with_growth_policy(|cap: usize, _| cap * 3);
+        l.push(4);
+        l.push(5);
+        assert_eq!(l.buf.cap(), 12);
+        assert_eq!(l.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn zst_list_works() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static DROPS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Marker;
+
+        impl Drop for Marker {
+            fn drop(&mut self) {
+                DROPS.with(|d| d.set(d.get() + 1));
+            }
+        }
+
+        let mut l = ArrayList::<()>::new();
+        for _ in 0..1000 {
+            l.push(());
+        }
+        l.push_front(());
+        assert_eq!(l.len, 1001);
+        assert_eq!(l[1000], ());
+        assert_eq!(l.pop(), Some(()));
+        assert_eq!(l.into_iter().count(), 1000);
+
+        let mut l = ArrayList::<Marker>::new();
+        l.push(Marker);
+        l.push(Marker);
+        l.push(Marker);
+        assert_eq!(l.pop(), Some(Marker));
+        assert_eq!(DROPS.with(|d| d.get()), 2);
+        let mut i = l.into_iter();
+        assert_eq!(i.next(), Some(Marker));
+        assert_eq!(DROPS.with(|d| d.get()), 4);
+        drop(i);
+        assert_eq!(DROPS.with(|d| d.get()), 5);
+    }
+
 }
@@ -0,0 +1,645 @@
+/*!
+Fixed-capacity caches that make room for new entries by evicting old ones.
+
+Both keep their entries in `DoublyLinkedList`s and index them with a `HashMap`
+of `NodeHandle`s, so a lookup can find an entry's node in O(1). `LruCache`
+evicts the least recently used entry, and moves a hit to the front of its one
+list in O(1). `LfuCache` evicts the least frequently used one, and the least
+recently used of those on a tie. Its lists are kept in a `BTreeMap` by use
+count, so a hit costs O(log b) for `b` distinct use counts.
+*/
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+
+use crate::doubly_linked_list::{DoublyLinkedList, NodeHandle};
+
+/// How a cache's lookups have gone since it was made or its stats were reset.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries pushed out to make room, not ones taken out by hand.
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// The fraction of lookups that were hits, or 0 if there haven't been any.
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+type EvictFn<K, V> = Box<dyn FnMut(K, V) + Send>;
+
+/**
+A cache that evicts the least recently used entry once it's full.
+
+`get` and `get_mut` count as uses and are tallied in `stats`; `peek` is neither.
+*/
+pub struct LruCache<K, V> {
+    // most recently used at the front
+    list: DoublyLinkedList<(K, V)>,
+    // every handle points into `list`
+    map: HashMap<K, NodeHandle<(K, V)>>,
+    cap: usize,
+    stats: CacheStats,
+    on_evict: Option<EvictFn<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /**
+    Makes an empty cache holding up to `cap` entries.
+
+    # Panics
+    Panics if `cap` is 0.
+    */
+    pub fn new(cap: usize) -> Self {
+        assert!(cap > 0, "a cache needs room for at least one entry");
+        LruCache {
+            list: DoublyLinkedList::new(),
+            map: HashMap::with_capacity(cap),
+            cap,
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    /// Calls `f` with every entry evicted to make room for a new one. Entries
+    /// taken out with `pop` or `pop_lru` aren't passed to it.
+    pub fn with_eviction_callback<F: FnMut(K, V) + Send + 'static>(mut self, f: F) -> Self {
+        self.on_evict = Some(Box::new(f));
+        self
+    }
+
+    /// The value for `key`, marking it as the most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    /// The value for `key`, marking it as the most recently used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(&h) = self.map.get(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        self.list.move_to_front(h);
+        Some(&mut self.entry_mut(h).1)
+    }
+
+    /// The value for `key`, without marking it as used or counting the lookup.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&h| &self.entry(h).1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts or replaces the value for `key`, making it the most recently used
+    /// entry. Evicts the least recently used entry if a new one doesn't fit.
+    /// Returns the replaced value, if there was one.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&h) = self.map.get(&key) {
+            self.list.move_to_front(h);
+            return Some(mem::replace(&mut self.entry_mut(h).1, value));
+        }
+
+        if self.map.len() == self.cap {
+            if let Some((k, v)) = self.pop_lru() {
+                self.stats.evictions += 1;
+                if let Some(f) = &mut self.on_evict {
+                    f(k, v);
+                }
+            }
+        }
+        let h = self.list.push_front((key.clone(), value));
+        self.map.insert(key, h);
+        None
+    }
+
+    /// Removes the entry for `key`, returning its value.
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let h = self.map.remove(key)?;
+        self.list.remove(h).map(|(_, v)| v)
+    }
+
+    /// Removes the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (k, v) = self.list.pop_back()?;
+        self.map.remove(&k);
+        Some((k, v))
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list = DoublyLinkedList::new();
+    }
+}
+
+impl<K, V> LruCache<K, V> {
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// The least recently used entry, which is the next to be evicted.
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.list.iter().next_back().map(|(k, v)| (k, v))
+    }
+
+    /// The entries from most to least recently used.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator + '_ {
+        self.list.iter().map(|(k, v)| (k, v))
+    }
+
+    fn entry(&self, h: NodeHandle<(K, V)>) -> &(K, V) {
+        let Some(entry) = self.list.get(h) else {
+            unreachable!("every handle in `map` is for a node in `list`");
+        };
+        entry
+    }
+
+    fn entry_mut(&mut self, h: NodeHandle<(K, V)>) -> &mut (K, V) {
+        let Some(entry) = self.list.get_mut(h) else {
+            unreachable!("every handle in `map` is for a node in `list`");
+        };
+        entry
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// one list of entries per use count
+type Buckets<K, V> = BTreeMap<usize, DoublyLinkedList<(K, V)>>;
+
+/// Where an `LfuCache` entry lives: the bucket for its use count, and its node there.
+struct Slot<K, V> {
+    uses: usize,
+    handle: NodeHandle<(K, V)>,
+}
+
+/**
+A cache that evicts the least frequently used entry once it's full, and the
+least recently used of those on a tie.
+
+Entries are kept in one list per use count, most recently used at the front,
+and the lists are ordered by use count so the next one to evict is always at
+the back of the first list. A new entry starts with one use; `get`, `get_mut`
+and replacing its value with `put` each add one. `peek` doesn't. Adding a use
+moves the entry into the next list, which is a `BTreeMap` lookup, so it's
+O(log b) for `b` distinct use counts rather than O(1).
+*/
+pub struct LfuCache<K, V> {
+    buckets: Buckets<K, V>,
+    // every handle points into the bucket for its `uses`, which is never empty
+    map: HashMap<K, Slot<K, V>>,
+    cap: usize,
+    stats: CacheStats,
+    on_evict: Option<EvictFn<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    /**
+    Makes an empty cache holding up to `cap` entries.
+
+    # Panics
+    Panics if `cap` is 0.
+    */
+    pub fn new(cap: usize) -> Self {
+        assert!(cap > 0, "a cache needs room for at least one entry");
+        LfuCache {
+            buckets: BTreeMap::new(),
+            map: HashMap::with_capacity(cap),
+            cap,
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    /// Calls `f` with every entry evicted to make room for a new one. Entries
+    /// taken out with `pop` or `pop_lfu` aren't passed to it.
+    pub fn with_eviction_callback<F: FnMut(K, V) + Send + 'static>(mut self, f: F) -> Self {
+        self.on_evict = Some(Box::new(f));
+        self
+    }
+
+    /// The value for `key`, counting a use of it.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    /// The value for `key`, counting a use of it.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(slot) = self.map.get_mut(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        Some(Self::bump(&mut self.buckets, slot))
+    }
+
+    /// The value for `key`, without counting a use or the lookup.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map
+            .get(key)
+            .and_then(|s| self.buckets[&s.uses].get(s.handle))
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// How many times `key` has been used.
+    pub fn frequency<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|s| s.uses)
+    }
+
+    /// Inserts the value for `key`, or replaces it and counts a use. Evicts the
+    /// least frequently used entry if a new one doesn't fit. Returns the
+    /// replaced value, if there was one.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(slot) = self.map.get_mut(&key) {
+            return Some(mem::replace(Self::bump(&mut self.buckets, slot), value));
+        }
+
+        if self.map.len() == self.cap {
+            if let Some((k, v)) = self.pop_lfu() {
+                self.stats.evictions += 1;
+                if let Some(f) = &mut self.on_evict {
+                    f(k, v);
+                }
+            }
+        }
+        let handle = self.buckets.entry(1).or_default().push_front((key.clone(), value));
+        self.map.insert(key, Slot { uses: 1, handle });
+        None
+    }
+
+    /// Removes the entry for `key`, returning its value.
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.map.remove(key)?;
+        Some(Self::unbucket(&mut self.buckets, &slot).1)
+    }
+
+    /// Removes the least frequently used entry, the least recently used of them on a tie.
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        let mut first = self.buckets.first_entry()?;
+        let (k, v) = first.get_mut().pop_back()?;
+        if first.get().is_empty() {
+            first.remove();
+        }
+        self.map.remove(&k);
+        Some((k, v))
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.buckets.clear();
+    }
+
+    /// Moves the entry at `slot` to the front of the bucket for one more use,
+    /// returning its value.
+    fn bump<'a>(buckets: &'a mut Buckets<K, V>, slot: &mut Slot<K, V>) -> &'a mut V {
+        let entry = Self::unbucket(buckets, slot);
+        slot.uses = slot.uses.saturating_add(1);
+        let list = buckets.entry(slot.uses).or_default();
+        slot.handle = list.push_front(entry);
+        let Some((_, v)) = list.front_mut() else {
+            unreachable!("the entry was just pushed");
+        };
+        v
+    }
+
+    /// Takes the entry at `slot` out of its bucket, dropping the bucket if it empties.
+    fn unbucket(buckets: &mut Buckets<K, V>, slot: &Slot<K, V>) -> (K, V) {
+        let Some(list) = buckets.get_mut(&slot.uses) else {
+            unreachable!("an entry's bucket is never empty");
+        };
+        let Some(entry) = list.remove(slot.handle) else {
+            unreachable!("every slot's handle is for a node in the bucket for its `uses`");
+        };
+        if list.is_empty() {
+            buckets.remove(&slot.uses);
+        }
+        entry
+    }
+}
+
+impl<K, V> LfuCache<K, V> {
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// The entry that would be evicted next.
+    pub fn peek_lfu(&self) -> Option<(&K, &V)> {
+        let list = self.buckets.values().next()?;
+        list.iter().next_back().map(|(k, v)| (k, v))
+    }
+
+    /// The entries from most to least frequently used, most recently used first on a tie.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.buckets.values().rev().flatten().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LfuCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::rng;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut c = LruCache::new(3);
+        c.put(1, "a");
+        c.put(2, "b");
+        c.put(3, "c");
+        assert_eq!(c.get(&1), Some(&"a"));
+        // 2 is now the oldest, and peeking doesn't change that
+        assert_eq!(c.peek(&2), Some(&"b"));
+        assert_eq!(c.peek_lru(), Some((&2, &"b")));
+        c.put(4, "d");
+
+        assert!(!c.contains_key(&2));
+        assert_eq!(c.len(), 3);
+        assert_eq!(c.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec![4, 1, 3]);
+        assert_eq!(c.put(3, "C"), Some("c"));
+        assert_eq!(c.pop_lru(), Some((1, "a")));
+        assert_eq!(c.pop(&4), Some("d"));
+        assert_eq!(c.pop(&4), None);
+        assert_eq!(format!("{c:?}"), r#"{3: "C"}"#);
+
+        c.clear();
+        assert!(c.is_empty());
+        assert_eq!(c.pop_lru(), None);
+    }
+
+    #[test]
+    fn lru_counts_hits_and_calls_back_on_eviction() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&evicted);
+        let mut c = LruCache::new(2).with_eviction_callback(move |k, v| log.lock().unwrap().push((k, v)));
+
+        c.put("x".to_string(), 1);
+        c.put("y".to_string(), 2);
+        *c.get_mut("x").unwrap() += 10;
+        assert_eq!(c.get("z"), None);
+        c.put("z".to_string(), 3);
+        // explicit removals aren't evictions
+        c.pop_lru();
+
+        assert_eq!(*evicted.lock().unwrap(), vec![("y".to_string(), 2)]);
+        assert_eq!(
+            c.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1,
+            }
+        );
+        assert_eq!(c.stats().hit_ratio(), 0.5);
+        c.reset_stats();
+        assert_eq!(c.stats().hit_ratio(), 0.0);
+    }
+
+    #[test]
+    fn lfu_evicts_least_frequently_used() {
+        let mut c = LfuCache::new(3);
+        c.put('a', 1);
+        c.put('b', 2);
+        c.put('c', 3);
+        c.get(&'a');
+        c.get(&'a');
+        c.get(&'b');
+        // 'c' has the fewest uses
+        c.put('d', 4);
+        assert!(!c.contains_key(&'c'));
+
+        // 'd' and 'b' would tie after this, and 'd' is now the least recent of them
+        c.get(&'d');
+        c.get(&'b');
+        assert_eq!(c.frequency(&'a'), Some(3));
+        assert_eq!(c.frequency(&'d'), Some(2));
+        assert_eq!(c.peek_lfu(), Some((&'d', &4)));
+        assert_eq!(c.put('a', 10), Some(1));
+        assert_eq!(c.frequency(&'a'), Some(4));
+        assert_eq!(c.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec!['a', 'b', 'd']);
+
+        c.put('e', 5);
+        assert_eq!(c.peek(&'d'), None);
+        assert_eq!(c.pop_lfu(), Some(('e', 5)));
+        assert_eq!(c.pop(&'b'), Some(2));
+        assert_eq!(c.len(), 1);
+        assert_eq!(
+            c.stats(),
+            CacheStats {
+                hits: 5,
+                misses: 0,
+                evictions: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn lfu_calls_back_on_eviction() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&evicted);
+        let mut c = LfuCache::new(1).with_eviction_callback(move |k, v| log.lock().unwrap().push((k, v)));
+        c.put(1, 1);
+        c.put(2, 2);
+        c.put(3, 3);
+        assert_eq!(*evicted.lock().unwrap(), vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_capacity_panics() {
+        LruCache::<u8, u8>::new(0);
+    }
+
+    #[test]
+    fn caches_are_send() {
+        fn is_send<T: Send>() {}
+        is_send::<LruCache<String, Vec<u8>>>();
+        is_send::<LfuCache<String, Vec<u8>>>();
+    }
+
+    #[test]
+    fn lru_matches_a_naive_model() {
+        let mut next = rng(0x9e37_79b9_7f4a_7c15);
+        let mut c = LruCache::new(8);
+        // most recently used last
+        let mut model: Vec<(usize, usize)> = Vec::new();
+
+        for i in 0..5000 {
+            let k = next() % 16;
+            match next() % 4 {
+                0 | 1 => {
+                    let found = model.iter().position(|e| e.0 == k).map(|p| model.remove(p));
+                    if let Some(e) = found {
+                        model.push(e);
+                    }
+                    assert_eq!(c.get(&k), found.as_ref().map(|e| &e.1));
+                }
+                2 => {
+                    let old = model.iter().position(|e| e.0 == k).map(|p| model.remove(p).1);
+                    if old.is_none() && model.len() == 8 {
+                        model.remove(0);
+                    }
+                    model.push((k, i));
+                    assert_eq!(c.put(k, i), old);
+                }
+                _ => {
+                    let old = model.iter().position(|e| e.0 == k).map(|p| model.remove(p).1);
+                    assert_eq!(c.pop(&k), old);
+                }
+            }
+            assert!(c.iter().map(|(k, v)| (*k, *v)).eq(model.iter().rev().copied()));
+        }
+    }
+
+    #[test]
+    fn lfu_matches_a_naive_model() {
+        let mut next = rng(0x2545_f491_4f6c_dd1d);
+        let mut c = LfuCache::new(8);
+        // (key, value, uses, last used)
+        let mut model: Vec<(usize, usize, usize, usize)> = Vec::new();
+
+        for i in 0..5000 {
+            let k = next() % 16;
+            let found = model.iter().position(|e| e.0 == k);
+            match next() % 4 {
+                0 | 1 => {
+                    if let Some(p) = found {
+                        model[p].2 += 1;
+                        model[p].3 = i;
+                    }
+                    assert_eq!(c.get(&k), found.map(|p| &model[p].1));
+                }
+                2 => {
+                    let old = match found {
+                        Some(p) => {
+                            model[p].2 += 1;
+                            model[p].3 = i;
+                            Some(mem::replace(&mut model[p].1, i))
+                        }
+                        None => {
+                            if model.len() == 8 {
+                                let victim = (0..8).min_by_key(|&p| (model[p].2, model[p].3)).unwrap();
+                                model.remove(victim);
+                            }
+                            model.push((k, i, 1, i));
+                            None
+                        }
+                    };
+                    assert_eq!(c.put(k, i), old);
+                }
+                _ => {
+                    assert_eq!(c.pop(&k), found.map(|p| model.remove(p).1));
+                }
+            }
+
+            model.sort_by_key(|e| std::cmp::Reverse((e.2, e.3)));
+            assert!(c.iter().map(|(k, v)| (*k, *v)).eq(model.iter().map(|e| (e.0, e.1))));
+            for e in &model {
+                assert_eq!(c.frequency(&e.0), Some(e.2));
+            }
+        }
+    }
+}
@@ -1,138 +1,802 @@
 #![allow(dead_code)]
-use std::sync::{Arc, Mutex};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 
-type Link<T> = Option<Arc<Mutex<Node<T>>>>;
+use crate::array_list::ArrayList;
+
+pub mod concurrent;
+
+type Link<T> = Option<NonNull<Node<T>>>;
 
-#[derive(Debug)]
 pub struct DoublyLinkedList<T> {
     len: usize,
     first: Link<T>,
     last: Link<T>,
+    // the stamp of the node holding each slot, or `VACANT`. Handles are checked against these
+    // rather than against their node, so a node can be freed as soon as it's removed
+    slots: ArrayList<u64>,
+    // the `VACANT` slots, which new nodes take before `slots` grows
+    vacant: ArrayList<usize>,
+    // the last stamp handed out, so every node that takes a slot gets a new one
+    stamp: u64,
+    // what this list's handles and nodes carry; a new one is taken whenever `slots` stops
+    // matching the nodes that are in the list
+    id: u64,
+    // the list owns its nodes, as if they were boxed
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// ids are never reused, so a handle can't match a list it didn't come from
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed)
 }
 
-#[derive(Debug)]
+// never a stamp, since stamps start at 1
+const VACANT: u64 = 0;
+
 struct Node<T> {
     value: T,
     prev: Link<T>,
     next: Link<T>,
+    // the list whose `slots` this node has a slot in, and which one. Nodes moved in from
+    // another list, or left behind by a new id, have none until a handle to them is made
+    owner: u64,
+    slot: usize,
 }
 
 impl<T> Node<T> {
-    fn new(value: T) -> Self {
-        Node {
+    fn alloc(value: T, owner: u64, slot: usize) -> NonNull<Self> {
+        NonNull::from(Box::leak(Box::new(Node {
             value,
             prev: None,
             next: None,
-        }
+            owner,
+            slot,
+        })))
+    }
+
+    /// # Safety
+    /// `n` must be in a list.
+    unsafe fn value<'a>(n: NonNull<Self>) -> &'a T {
+        &(*n.as_ptr()).value
+    }
+
+    /// # Safety
+    /// Same as `value`.
+    unsafe fn value_mut<'a>(n: NonNull<Self>) -> &'a mut T {
+        &mut (*n.as_ptr()).value
     }
 }
 
+// the list hands out its values like a `Box` would
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
 impl<T> DoublyLinkedList<T> {
     pub fn new() -> Self {
         DoublyLinkedList {
             len: 0,
             first: None,
             last: None,
+            slots: ArrayList::new(),
+            vacant: ArrayList::new(),
+            stamp: VACANT,
+            id: next_id(),
+            marker: PhantomData,
         }
     }
 
-    pub fn push_front(&mut self, value: T) {
-        let mut n = Node::new(value);
-        if let Some(old_first) = self.first.take() {
-            n.next = Some(old_first.clone());
-            let link = Arc::new(Mutex::new(n));
-            self.first = Some(link.clone());
-            old_first.lock().unwrap().prev = Some(link);
-        } else {
-            let link = Arc::new(Mutex::new(n));
-            self.first = Some(link.clone());
-            self.last = Some(link);
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds `value` to the front, returning a handle to its node.
+    pub fn push_front(&mut self, value: T) -> NodeHandle<T> {
+        let n = self.alloc(value);
+        unsafe { self.link_between(n, None, self.first) };
+        self.handle(n)
+    }
+
+    /// Adds `value` to the back, returning a handle to its node.
+    pub fn push_back(&mut self, value: T) -> NodeHandle<T> {
+        let n = self.alloc(value);
+        unsafe { self.link_between(n, self.last, None) };
+        self.handle(n)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.first.map(|n| unsafe { self.take(n) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.last.map(|n| unsafe { self.take(n) })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.first.map(|n| unsafe { Node::value(n) })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.first.map(|n| unsafe { Node::value_mut(n) })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.last.map(|n| unsafe { Node::value(n) })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.last.map(|n| unsafe { Node::value_mut(n) })
+    }
+
+    /// An unlinked node for `value`, with a slot of its own.
+    fn alloc(&mut self, value: T) -> NonNull<Node<T>> {
+        Node::alloc(value, self.id, self.claim_slot())
+    }
+
+    /// Stamps a vacant slot, or a new one, and returns it.
+    fn claim_slot(&mut self) -> usize {
+        self.stamp += 1;
+        match self.vacant.pop() {
+            Some(slot) => {
+                self.slots[slot] = self.stamp;
+                slot
+            }
+            None => {
+                self.slots.push(self.stamp);
+                self.slots.len() - 1
+            }
         }
-        self.len += 1;
     }
 
-    pub fn push_back(&mut self, value: T) {
-        let mut n = Node::new(value);
-        if let Some(old_last) = self.last.take() {
-            n.prev = Some(old_last.clone());
-            let link = Arc::new(Mutex::new(n));
-            self.last = Some(link.clone());
-            old_last.lock().unwrap().next = Some(link);
-        } else {
-            let link = Arc::new(Mutex::new(n));
-            self.first = Some(link.clone());
-            self.last = Some(link);
+    /// Unlinks `n`, which must be in this list, and frees it, which invalidates
+    /// every handle to it by vacating its slot.
+    unsafe fn take(&mut self, n: NonNull<Node<T>>) -> T {
+        self.unlink(n);
+        let node = Box::from_raw(n.as_ptr());
+        if node.owner == self.id {
+            self.slots[node.slot] = VACANT;
+            self.vacant.push(node.slot);
         }
-        self.len += 1;
+        node.value
     }
 
-    pub fn pop_front(&mut self) -> Option<T> {
-        if let Some(first) = self.first.take() {
-            first
-                .lock()
-                .map(|mut old_first| {
-                    if let Some(first_node) = old_first.next.take() {
-                        self.first = Some(first_node.clone());
-                        let mut first_node = first_node.lock().unwrap();
-                        first_node.prev = None;
-                    } else {
-                        self.last.take();
-                    }
-                })
-                .unwrap();
+    /// A handle to `n`, which must be in this list, giving it a slot first if it has none.
+    fn handle(&mut self, n: NonNull<Node<T>>) -> NodeHandle<T> {
+        let node = unsafe { &mut *n.as_ptr() };
+        if node.owner != self.id {
+            node.owner = self.id;
+            node.slot = self.claim_slot();
+        }
+        NodeHandle {
+            node: n,
+            list: self.id,
+            slot: node.slot,
+            stamp: self.slots[node.slot],
+        }
+    }
 
-            self.len -= 1;
+    /// The node `handle` points to, if it's still in this list. Only `slots` is
+    /// read, so this is safe even if the node has been freed.
+    fn node(&self, handle: NodeHandle<T>) -> Link<T> {
+        let live = handle.list == self.id && self.slots.get(handle.slot) == Some(&handle.stamp);
+        live.then_some(handle.node)
+    }
 
-            Arc::into_inner(first)
-                .and_then(|m| m.into_inner().ok())
-                .map(|n| n.value)
-        } else {
-            None
+    /// Removes the element `handle` points to, in O(1), or returns `None` if
+    /// it's already gone or the handle is for another list.
+    pub fn remove(&mut self, handle: NodeHandle<T>) -> Option<T> {
+        let n = self.node(handle)?;
+        Some(unsafe { self.take(n) })
+    }
+
+    /// Moves the element `handle` points to to the front, in O(1). The handle
+    /// stays valid. Returns `false` if the handle is stale, like `remove`.
+    pub fn move_to_front(&mut self, handle: NodeHandle<T>) -> bool {
+        let Some(n) = self.node(handle) else {
+            return false;
+        };
+        if self.first != Some(n) {
+            unsafe {
+                self.unlink(n);
+                self.link_between(n, None, self.first);
+            }
         }
+        true
     }
 
-    pub fn pop_back(&mut self) -> Option<T> {
-        if let Some(last) = self.last.take() {
-            last.lock()
-                .map(|mut old_last| {
-                    if let Some(last_node) = old_last.prev.take() {
-                        self.last = Some(last_node.clone());
-                        let mut last_node = last_node.lock().unwrap();
-                        last_node.next = None;
-                    } else {
-                        self.first.take();
-                    }
-                })
-                .unwrap();
+    /// Moves the element `handle` points to to the back, in O(1). The handle
+    /// stays valid. Returns `false` if the handle is stale, like `remove`.
+    pub fn move_to_back(&mut self, handle: NodeHandle<T>) -> bool {
+        let Some(n) = self.node(handle) else {
+            return false;
+        };
+        if self.last != Some(n) {
+            unsafe {
+                self.unlink(n);
+                self.link_between(n, self.last, None);
+            }
+        }
+        true
+    }
+
+    /// The element `handle` points to, or `None` if the handle is stale.
+    pub fn get(&self, handle: NodeHandle<T>) -> Option<&T> {
+        self.node(handle).map(|n| unsafe { Node::value(n) })
+    }
 
-            self.len -= 1;
+    /// The element `handle` points to, or `None` if the handle is stale.
+    pub fn get_mut(&mut self, handle: NodeHandle<T>) -> Option<&mut T> {
+        self.node(handle).map(|n| unsafe { Node::value_mut(n) })
+    }
 
-            Arc::into_inner(last)
-                .and_then(|m| m.into_inner().ok())
-                .map(|n| n.value)
-        } else {
-            None
+    /// Whether `handle` still points to an element of this list.
+    pub fn contains_handle(&self, handle: NodeHandle<T>) -> bool {
+        self.node(handle).is_some()
+    }
+
+    /// Links the unlinked node `n` in between `prev` and `next`, which must be
+    /// neighbours in this list, or `None` for the ends.
+    unsafe fn link_between(&mut self, n: NonNull<Node<T>>, prev: Link<T>, next: Link<T>) {
+        (*n.as_ptr()).prev = prev;
+        (*n.as_ptr()).next = next;
+        match prev {
+            Some(p) => (*p.as_ptr()).next = Some(n),
+            None => self.first = Some(n),
+        }
+        match next {
+            Some(nx) => (*nx.as_ptr()).prev = Some(n),
+            None => self.last = Some(n),
+        }
+        self.len += 1;
+    }
+
+    /// Moves every node of `l` in between `prev` and `next`, like `link_between`.
+    /// `l`'s handles don't carry over, since its id and slots go with it.
+    unsafe fn splice_between(&mut self, mut l: Self, prev: Link<T>, next: Link<T>) {
+        let (Some(first), Some(last)) = (l.first.take(), l.last.take()) else {
+            return;
+        };
+
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+        match prev {
+            Some(p) => (*p.as_ptr()).next = Some(first),
+            None => self.first = Some(first),
+        }
+        match next {
+            Some(nx) => (*nx.as_ptr()).prev = Some(last),
+            None => self.last = Some(last),
+        }
+        self.len += l.len;
+        l.len = 0;
+    }
+
+    /// Takes the node `n`, which must be in this list, out of it without freeing it.
+    unsafe fn unlink(&mut self, n: NonNull<Node<T>>) {
+        let prev = (*n.as_ptr()).prev.take();
+        let next = (*n.as_ptr()).next.take();
+        match prev {
+            Some(p) => (*p.as_ptr()).next = next,
+            None => self.first = next,
+        }
+        match next {
+            Some(nx) => (*nx.as_ptr()).prev = prev,
+            None => self.last = prev,
+        }
+        self.len -= 1;
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.first,
+            back: self.last,
+            len: self.len,
+            marker: PhantomData,
         }
     }
 
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.first,
+            back: self.last,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Moves every element of `l` to the back of this list.
     pub fn append(&mut self, l: Self) {
-        if let Some(last) = self.last.take() {
-            last.lock()
-                .map(|mut old_last| {
-                    old_last.next = l.first.clone();
-                })
-                .unwrap();
+        unsafe { self.splice_between(l, self.last, None) }
+    }
+
+    /// Moves every element of `l` to the front of this list.
+    pub fn prepend(&mut self, l: Self) {
+        unsafe { self.splice_between(l, None, self.first) }
+    }
+
+    /// A cursor on the first element, or on the "ghost" position if the list is empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.first,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// A cursor on the last element, or on the "ghost" position if the list is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.last,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    /// Splits the list in two at `at`, returning everything from `at` onwards.
+    /// Walks in from whichever end is closer. Unless `at` is 0 or `len`, every
+    /// handle into the list goes stale.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if at > self.len {
+            panic!("`at` split index (is {at}) should be <= len (is {})", self.len);
+        }
+        if at == self.len {
+            return Self::new();
+        }
+        if at == 0 {
+            return mem::take(self);
+        }
+
+        // the last node that stays, with 0 < at < len
+        let keep = if at <= self.len / 2 {
+            let mut n = self.first;
+            for _ in 1..at {
+                n = n.and_then(|n| unsafe { (*n.as_ptr()).next });
+            }
+            n
         } else {
-            self.first = l.first.clone();
+            let mut n = self.last;
+            for _ in at..self.len {
+                n = n.and_then(|n| unsafe { (*n.as_ptr()).prev });
+            }
+            n
+        };
+        let Some(keep) = keep else {
+            unreachable!("a list longer than `at` has a node at `at - 1`");
+        };
+
+        let first = unsafe { (*keep.as_ptr()).next.take() };
+        if let Some(first) = first {
+            unsafe { (*first.as_ptr()).prev = None };
         }
-        self.len += l.len;
-        self.last = l.last.clone();
+        let mut rest = Self::new();
+        rest.len = self.len - at;
+        rest.first = first;
+        rest.last = self.last;
+        self.last = Some(keep);
+        self.len = at;
+        // handles can't tell which half their node went to, so none of them carry over
+        self.id = next_id();
+        self.slots.clear();
+        self.vacant.clear();
+        rest
+    }
+
+    /**
+    Gives back the memory kept for handle slots beyond the last one in use.
+    Removed elements free their nodes straight away, so there's one slot left
+    over for each element the list held at its longest. Every handle stays valid.
+    */
+    pub fn shrink_to_fit(&mut self) {
+        while self.slots.last() == Some(&VACANT) {
+            self.slots.pop();
+        }
+        let len = self.slots.len();
+        self.vacant.retain(|&slot| slot < len);
+        self.slots.shrink_to_fit();
+        self.vacant.shrink_to_fit();
+    }
+
+    /**
+    Walks the list in both directions and panics if any link disagrees with its
+    neighbour, if the ends aren't where `first` and `last` say, or if `len` is
+    wrong. It's O(n), so it's meant for tests.
+    */
+    pub fn debug_validate(&self) {
+        let mut count = 0;
+        let mut prev: Link<T> = None;
+        let mut next = self.first;
+
+        while let Some(n) = next {
+            count += 1;
+            assert!(count <= self.len, "more nodes than len ({})", self.len);
+            unsafe {
+                assert_eq!((*n.as_ptr()).prev, prev, "node {} has the wrong prev", count - 1);
+                next = (*n.as_ptr()).next;
+            }
+            prev = Some(n);
+        }
+
+        assert_eq!(count, self.len, "len doesn't match the node count");
+        assert_eq!(self.last, prev, "last isn't the final node");
+        if let Some(first) = self.first {
+            assert!(unsafe { (*first.as_ptr()).prev.is_none() }, "first has a prev");
+        }
+    }
+}
+
+/// Frees one node at a time, like `LinkedList`.
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        let mut next = self.first.take();
+        while let Some(n) = next {
+            // every node came from `Node::alloc`, and none is freed twice
+            let node = unsafe { Box::from_raw(n.as_ptr()) };
+            next = node.next;
+        }
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for DoublyLinkedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for DoublyLinkedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        self.iter().for_each(|v| v.hash(state));
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.push_back(v);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut l = DoublyLinkedList::new();
+        l.extend(iter);
+        l
+    }
+}
+
+impl<T> From<Vec<T>> for DoublyLinkedList<T> {
+    fn from(v: Vec<T>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for DoublyLinkedList<T> {
+    fn from(arr: [T; N]) -> Self {
+        arr.into_iter().collect()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Steps `link` one node towards `next` (or `prev`), returning a pointer to the
+/// value it was on. Only the value is ever borrowed, so the links stay free to read.
+fn step<T>(link: &mut Link<T>, forward: bool) -> Option<*mut T> {
+    let n = link.take()?.as_ptr();
+    unsafe {
+        *link = if forward { (*n).next } else { (*n).prev };
+        Some(&mut (*n).value as *mut T)
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        step(&mut self.front, true).map(|p| unsafe { &*p })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        step(&mut self.back, false).map(|p| unsafe { &*p })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut T>,
+}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // `len` keeps the two ends from ever handing out the same node
+        self.len -= 1;
+        step(&mut self.front, true).map(|p| unsafe { &mut *p })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        step(&mut self.back, false).map(|p| unsafe { &mut *p })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/**
+Points at one element of a list, so it can be found again in O(1) no matter
+where it moves. Handles are checked: once the element is removed, or the
+handle is used with another list, the methods that take one return `None`
+(or `false`) instead of touching it.
+*/
+pub struct NodeHandle<T> {
+    node: NonNull<Node<T>>,
+    list: u64,
+    slot: usize,
+    stamp: u64,
+}
+
+// a handle is never dereferenced without the list that owns its node
+unsafe impl<T: Send> Send for NodeHandle<T> {}
+unsafe impl<T: Sync> Sync for NodeHandle<T> {}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeHandle<T> {}
+
+impl<T> PartialEq for NodeHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node && self.list == other.list && self.slot == other.slot && self.stamp == other.stamp
+    }
+}
+
+impl<T> Eq for NodeHandle<T> {}
+
+impl<T> fmt::Debug for NodeHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHandle")
+            .field("node", &self.node)
+            .field("list", &self.list)
+            .field("slot", &self.slot)
+            .field("stamp", &self.stamp)
+            .finish()
+    }
+}
+
+/**
+Edits a list in place, like std's `CursorMut`. The cursor is either on an
+element or on the "ghost" position between the last element and the first,
+so moving past either end wraps around through the ghost.
+*/
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    // `None` on the ghost
+    current: Link<T>,
+    // `list.len` on the ghost
+    index: usize,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// The index of the current element, or `None` on the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|n| unsafe { Node::value_mut(n) })
+    }
+
+    fn next_node(&self) -> Link<T> {
+        match self.current {
+            Some(n) => unsafe { (*n.as_ptr()).next },
+            None => self.list.first,
+        }
+    }
+
+    fn prev_node(&self) -> Link<T> {
+        match self.current {
+            Some(n) => unsafe { (*n.as_ptr()).prev },
+            None => self.list.last,
+        }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_node().map(|n| unsafe { Node::value_mut(n) })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.prev_node().map(|n| unsafe { Node::value_mut(n) })
+    }
+
+    pub fn move_next(&mut self) {
+        let on_ghost = self.current.is_none();
+        self.current = self.next_node();
+        self.index = match self.current {
+            None => self.list.len,
+            Some(_) if on_ghost => 0,
+            Some(_) => self.index + 1,
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        let on_ghost = self.current.is_none();
+        self.current = self.prev_node();
+        self.index = match self.current {
+            None => self.list.len,
+            Some(_) if on_ghost => self.list.len - 1,
+            Some(_) => self.index - 1,
+        };
+    }
+
+    /// Inserts `value` after the current element, or at the front on the ghost.
+    pub fn insert_after(&mut self, value: T) -> NodeHandle<T> {
+        let n = self.list.alloc(value);
+        unsafe { self.list.link_between(n, self.current, self.next_node()) };
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+        self.list.handle(n)
+    }
+
+    /// Inserts `value` before the current element, or at the back on the ghost.
+    pub fn insert_before(&mut self, value: T) -> NodeHandle<T> {
+        let n = self.list.alloc(value);
+        unsafe { self.list.link_between(n, self.prev_node(), self.current) };
+        // either way there's one more element before the cursor
+        self.index += 1;
+        self.list.handle(n)
+    }
+
+    /// Removes the current element and moves onto the next one. Does nothing on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let n = self.current?;
+        self.current = self.next_node();
+        let value = unsafe { self.list.take(n) };
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+        Some(value)
+    }
+
+    /// Moves all of `other` in after the current element, or at the front on the ghost.
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        let next = self.next_node();
+        unsafe { self.list.splice_between(other, self.current, next) };
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    /// Moves all of `other` in before the current element, or at the back on the ghost.
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        let added = other.len;
+        let prev = self.prev_node();
+        unsafe { self.list.splice_between(other, prev, self.current) };
+        self.index += added;
+    }
+
+    /// A handle to the current element, which outlives the cursor.
+    pub fn current_handle(&mut self) -> Option<NodeHandle<T>> {
+        self.current.map(|n| self.list.handle(n))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::rng;
 
     #[test]
     fn linked_list_works() {
@@ -173,10 +837,412 @@ mod tests {
         l2.push_front(3); // 3
         l2.push_back(7); // 3 7
         l.append(l2); // 5 10 3 7
+        l.debug_validate();
         assert_eq!(l.len, 4);
         assert_eq!(l.pop_front(), Some(5));
         assert_eq!(l.pop_front(), Some(10));
         assert_eq!(l.pop_front(), Some(3));
         assert_eq!(l.pop_front(), Some(7));
     }
+
+    #[test]
+    fn linked_list_iter_works() {
+        let mut l = DoublyLinkedList::<usize>::new();
+        assert_eq!(l.iter().next(), None);
+        l.push_back(2);
+        l.push_back(3);
+        l.push_front(1);
+        assert_eq!(l.iter().len(), 3);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(l.iter().rev().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+
+        let mut it = l.iter_mut();
+        *it.next().unwrap() = 10;
+        *it.next_back().unwrap() = 30;
+        *it.next().unwrap() = 20;
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next_back(), None);
+
+        for v in &mut l {
+            *v += 1;
+        }
+        assert_eq!((&l).into_iter().copied().collect::<Vec<_>>(), vec![11, 21, 31]);
+        assert_eq!(l.pop_front(), Some(11));
+        assert_eq!(l.pop_back(), Some(31));
+    }
+
+    #[test]
+    fn nodes_are_freed() {
+        let marker = std::rc::Rc::new(());
+        let mut l = DoublyLinkedList::new();
+        for _ in 0..10 {
+            l.push_back(marker.clone());
+            l.push_front(marker.clone());
+        }
+        l.pop_back();
+        l.pop_front();
+        let mut other = DoublyLinkedList::new();
+        other.push_back(marker.clone());
+        l.append(other);
+        assert_eq!(std::rc::Rc::strong_count(&marker), 20);
+        drop(l);
+        assert_eq!(std::rc::Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn list_is_send_and_sync() {
+        fn assert_send_sync<S: Send + Sync>() {}
+        assert_send_sync::<DoublyLinkedList<String>>();
+        assert_send_sync::<Iter<'_, String>>();
+        assert_send_sync::<IterMut<'_, String>>();
+
+        let l = DoublyLinkedList::from(vec![1, 2, 3]);
+        let sum = std::thread::spawn(move || l.iter().sum::<i32>()).join().unwrap();
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn append_links_both_ways() {
+        let mut l = DoublyLinkedList::from([1, 2]);
+        l.append(DoublyLinkedList::from([3, 4]));
+        l.debug_validate();
+        // walking back over the seam needs the appended first node's prev
+        assert_eq!(l.pop_back(), Some(4));
+        assert_eq!(l.pop_back(), Some(3));
+        assert_eq!(l.pop_back(), Some(2));
+        l.debug_validate();
+
+        // appending nothing mustn't lose the end of the list
+        l.append(DoublyLinkedList::new());
+        l.debug_validate();
+        l.push_back(5);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1, 5]);
+
+        let mut empty = DoublyLinkedList::new();
+        empty.append(l);
+        empty.debug_validate();
+        assert_eq!(empty.iter().rev().copied().collect::<Vec<_>>(), vec![5, 1]);
+    }
+
+    #[test]
+    fn split_off_and_prepend_work() {
+        let mut l = DoublyLinkedList::from([1, 2, 3, 4, 5]);
+        let mut back = l.split_off(4);
+        let mut middle = l.split_off(1);
+        for list in [&l, &middle, &back] {
+            list.debug_validate();
+        }
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(middle.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(back.iter().copied().collect::<Vec<_>>(), vec![5]);
+
+        assert!(back.split_off(1).is_empty());
+        let all = back.split_off(0);
+        back.debug_validate();
+        assert!(back.is_empty());
+
+        middle.prepend(l);
+        middle.prepend(DoublyLinkedList::new());
+        middle.append(all);
+        middle.debug_validate();
+        assert_eq!(middle.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(middle.iter().rev().copied().collect::<Vec<_>>(), vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds_panics() {
+        DoublyLinkedList::from([1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn random_edits_keep_invariants() {
+        use std::collections::VecDeque;
+
+        let mut next = rng(0x853c_49e6_748f_ea9b);
+
+        let mut l = DoublyLinkedList::new();
+        let mut model = VecDeque::new();
+
+        for i in 0..if cfg!(miri) { 200 } else { 3000 } {
+            match next() % 8 {
+                0 | 1 => {
+                    l.push_back(i);
+                    model.push_back(i);
+                }
+                2 => {
+                    l.push_front(i);
+                    model.push_front(i);
+                }
+                3 => assert_eq!(l.pop_front(), model.pop_front()),
+                4 => assert_eq!(l.pop_back(), model.pop_back()),
+                5 => {
+                    let at = next() % (model.len() + 1);
+                    let mut rest = l.split_off(at);
+                    let mut model_rest = model.split_off(at);
+                    rest.debug_validate();
+                    rest.push_back(i);
+                    model_rest.push_back(i);
+                    l.debug_validate();
+                    l.append(rest);
+                    model.append(&mut model_rest);
+                }
+                6 => {
+                    let at = next() % (model.len() + 1);
+                    let front = {
+                        let rest = l.split_off(at);
+                        mem::replace(&mut l, rest)
+                    };
+                    l.prepend(front);
+                }
+                _ => {
+                    let extra = DoublyLinkedList::from([i, i + 1]);
+                    if next().is_multiple_of(2) {
+                        l.append(extra);
+                        model.extend([i, i + 1]);
+                    } else {
+                        l.prepend(extra);
+                        model.push_front(i + 1);
+                        model.push_front(i);
+                    }
+                }
+            }
+
+            l.debug_validate();
+            assert!(l.iter().eq(model.iter()));
+        }
+    }
+
+    #[test]
+    fn cursor_walks_through_the_ghost() {
+        let mut l = DoublyLinkedList::from([1, 2, 3]);
+        let mut c = l.cursor_front_mut();
+        assert_eq!(c.index(), Some(0));
+        assert_eq!(c.peek_prev(), None);
+        c.move_next();
+        c.move_next();
+        assert_eq!(c.current(), Some(&mut 3));
+        assert_eq!(c.index(), Some(2));
+        c.move_next();
+        assert_eq!(c.index(), None);
+        assert_eq!(c.current(), None);
+        assert_eq!(c.peek_next(), Some(&mut 1));
+        assert_eq!(c.peek_prev(), Some(&mut 3));
+        c.move_next();
+        assert_eq!(c.index(), Some(0));
+        c.move_prev();
+        c.move_prev();
+        assert_eq!(c.current(), Some(&mut 3));
+        assert_eq!(c.index(), Some(2));
+
+        let mut empty = DoublyLinkedList::<u8>::new();
+        let mut c = empty.cursor_back_mut();
+        c.move_prev();
+        assert_eq!(c.index(), None);
+        c.insert_after(1);
+        c.insert_before(2);
+        assert_eq!(c.index(), None);
+        empty.debug_validate();
+        assert_eq!(empty.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn cursor_edits_work() {
+        let mut l = DoublyLinkedList::from([1, 2, 3, 4]);
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        assert_eq!(c.remove_current(), Some(2));
+        assert_eq!(c.current(), Some(&mut 3));
+        assert_eq!(c.index(), Some(1));
+        c.insert_before(20);
+        c.insert_after(30);
+        assert_eq!(c.index(), Some(2));
+        c.splice_before(DoublyLinkedList::from([21, 22]));
+        c.splice_after(DoublyLinkedList::from([31]));
+        assert_eq!(c.index(), Some(4));
+        assert_eq!(c.peek_next(), Some(&mut 31));
+
+        let mut b = l.cursor_back_mut();
+        assert_eq!(b.remove_current(), Some(4));
+        assert_eq!(b.index(), None);
+        assert_eq!(b.remove_current(), None);
+        b.splice_after(DoublyLinkedList::from([0]));
+        b.splice_before(DoublyLinkedList::from([5]));
+        assert_eq!(b.index(), None);
+        l.debug_validate();
+        assert_eq!(
+            l.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 20, 21, 22, 3, 31, 30, 5]
+        );
+    }
+
+    #[test]
+    fn handles_remove_and_move_in_place() {
+        let mut l = DoublyLinkedList::new();
+        let a = l.push_back("a");
+        let b = l.push_back("b");
+        let c = l.push_front("c");
+        let d = l.cursor_front_mut().insert_after("d");
+        // c d a b
+        assert!(l.move_to_front(b));
+        assert!(l.move_to_front(b));
+        assert!(l.move_to_back(c));
+        l.debug_validate();
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec!["b", "d", "a", "c"]);
+
+        *l.get_mut(a).unwrap() = "A";
+        assert_eq!(l.remove(d), Some("d"));
+        assert_eq!(l.get(a), Some(&"A"));
+        assert!(l.move_to_front(a));
+        l.debug_validate();
+        assert_eq!(l.remove(c), Some("c"));
+        l.debug_validate();
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec!["A", "b"]);
+
+        let mut c = l.cursor_back_mut();
+        assert_eq!(c.current_handle(), Some(b));
+        c.move_next();
+        assert_eq!(c.current_handle(), None);
+    }
+
+    #[test]
+    fn stale_handles_are_caught() {
+        let mut l = DoublyLinkedList::from([1, 2, 3]);
+        let a = l.push_back(4);
+        assert_eq!(l.remove(a), Some(4));
+        assert!(!l.contains_handle(a));
+        assert_eq!(l.remove(a), None);
+        assert_eq!(l.get(a), None);
+        assert!(!l.move_to_front(a));
+
+        // the freed slot gets reused, but with a new stamp the old handle doesn't match
+        let b = l.push_front(5);
+        assert_ne!(a, b);
+        assert_eq!(l.get(a), None);
+        assert_eq!(l.get(b), Some(&5));
+
+        let mut other = DoublyLinkedList::from([6]);
+        let c = other.push_back(7);
+        assert_eq!(l.get(c), None);
+        assert!(!l.move_to_back(c));
+        assert_eq!(other.get_mut(b), None);
+
+        // appended nodes keep their old list's handles, which no longer match anything
+        l.append(other);
+        assert_eq!(l.get(c), None);
+        assert_eq!(l.get(b), Some(&5));
+
+        let mut front = l.split_off(0);
+        assert_eq!(front.get(b), Some(&5));
+        let rest = front.split_off(2);
+        assert_eq!(front.get(b), None);
+        assert_eq!(rest.get(b), None);
+
+        let d = front.push_back(8);
+        front.pop_back();
+        front.shrink_to_fit();
+        assert_eq!(front.get(d), None);
+        // takes the slot `d` had, which shrinking gave back
+        let e = front.push_back(9);
+        assert_eq!(front.get(d), None);
+        front.shrink_to_fit();
+        assert_eq!(front.get(e), Some(&9));
+        front.debug_validate();
+    }
+
+    #[test]
+    fn removed_nodes_dont_pile_up() {
+        let mut l = DoublyLinkedList::new();
+        let kept = l.push_back(0);
+        for i in 1..100 {
+            l.push_back(i);
+        }
+        while l.len() > 1 {
+            l.pop_back();
+        }
+        // a queue's worth of churn reuses the slots its peak left behind
+        for i in 0..1000 {
+            l.push_back(i);
+            l.pop_back();
+        }
+        assert_eq!(l.slots.len(), 100);
+
+        l.shrink_to_fit();
+        assert_eq!(l.slots.len(), 1);
+        assert!(l.vacant.is_empty());
+        assert_eq!(l.get(kept), Some(&0));
+    }
+
+    #[test]
+    fn random_cursor_edits_match_vec() {
+        let mut next = rng(0x2f69_3b6c_1c2d_f5a1);
+
+        let mut l = DoublyLinkedList::new();
+        let mut model = Vec::new();
+        // the model's cursor, with `model.len()` as the ghost
+        let mut at = 0;
+
+        for i in 0..if cfg!(miri) { 200 } else { 3000 } {
+            let mut c = l.cursor_front_mut();
+            for _ in 0..at {
+                c.move_next();
+            }
+            if at == model.len() && !model.is_empty() {
+                // walking from the front only reaches the ghost after the last element
+                assert_eq!(c.index(), None);
+            }
+
+            match next() % 7 {
+                0 => {
+                    c.move_next();
+                    at = if at == model.len() { 0 } else { at + 1 };
+                    if at > model.len() {
+                        at = model.len();
+                    }
+                }
+                1 => {
+                    c.move_prev();
+                    at = if at == 0 { model.len() } else { at - 1 };
+                }
+                2 => {
+                    c.insert_after(i);
+                    if at == model.len() {
+                        model.insert(0, i);
+                        at = model.len();
+                    } else {
+                        model.insert(at + 1, i);
+                    }
+                }
+                3 => {
+                    c.insert_before(i);
+                    model.insert(at, i);
+                    at += 1;
+                }
+                4 => {
+                    let removed = c.remove_current();
+                    assert_eq!(removed, (at < model.len()).then(|| model.remove(at)));
+                }
+                5 => {
+                    c.splice_before(DoublyLinkedList::from([i, i + 1]));
+                    model.splice(at..at, [i, i + 1]);
+                    at += 2;
+                }
+                _ => {
+                    c.splice_after(DoublyLinkedList::from([i]));
+                    if at == model.len() {
+                        model.insert(0, i);
+                        at = model.len();
+                    } else {
+                        model.insert(at + 1, i);
+                    }
+                }
+            }
+
+            let index = c.index();
+            assert_eq!(index, (at < model.len()).then_some(at));
+            l.debug_validate();
+            assert!(l.iter().eq(model.iter()));
+        }
+    }
 }
+
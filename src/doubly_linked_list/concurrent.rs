@@ -0,0 +1,328 @@
+/*!
+A deque that threads can share, guarding a `DoublyLinkedList` with a mutex.
+
+Every operation takes the lock once, so each one is atomic on its own but a
+sequence of them isn't. Pops come in three flavours: `pop_front` returns right
+away, `pop_front_blocking` parks on a condvar until there's something to pop,
+and `pop_front_timeout` gives up after a while. Closing the deque wakes every
+blocked pop and lets them return `None` once it's empty, which is how
+consumers find out there's nothing more coming.
+*/
+
+use std::fmt;
+use std::mem;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::DoublyLinkedList;
+
+struct State<T> {
+    list: DoublyLinkedList<T>,
+    closed: bool,
+    // how many pops are parked, so pushes only pay for a notify when someone's waiting
+    waiting: usize,
+}
+
+pub struct ConcurrentDeque<T> {
+    state: Mutex<State<T>>,
+    // blocked pops wait here for a push or a close
+    not_empty: Condvar,
+}
+
+impl<T> ConcurrentDeque<T> {
+    pub fn new() -> Self {
+        Self::from(DoublyLinkedList::new())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn push_front(&self, value: T) {
+        let mut state = self.lock();
+        state.list.push_front(value);
+        self.wake(state);
+    }
+
+    pub fn push_back(&self, value: T) {
+        let mut state = self.lock();
+        state.list.push_back(value);
+        self.wake(state);
+    }
+
+    fn wake(&self, state: MutexGuard<'_, State<T>>) {
+        let waiting = state.waiting > 0;
+        drop(state);
+        if waiting {
+            self.not_empty.notify_one();
+        }
+    }
+
+    pub fn pop_front(&self) -> Option<T> {
+        self.lock().list.pop_front()
+    }
+
+    pub fn pop_back(&self) -> Option<T> {
+        self.lock().list.pop_back()
+    }
+
+    /// Pops the front, waiting for a push if the deque is empty. Returns `None`
+    /// only once the deque is closed and empty.
+    pub fn pop_front_blocking(&self) -> Option<T> {
+        self.pop_waiting(None, DoublyLinkedList::pop_front)
+    }
+
+    /// Pops the back, waiting for a push if the deque is empty. Returns `None`
+    /// only once the deque is closed and empty.
+    pub fn pop_back_blocking(&self) -> Option<T> {
+        self.pop_waiting(None, DoublyLinkedList::pop_back)
+    }
+
+    /// Like `pop_front_blocking`, but gives up and returns `None` after `timeout`.
+    pub fn pop_front_timeout(&self, timeout: Duration) -> Option<T> {
+        self.pop_waiting(Instant::now().checked_add(timeout), DoublyLinkedList::pop_front)
+    }
+
+    /// Like `pop_back_blocking`, but gives up and returns `None` after `timeout`.
+    pub fn pop_back_timeout(&self, timeout: Duration) -> Option<T> {
+        self.pop_waiting(Instant::now().checked_add(timeout), DoublyLinkedList::pop_back)
+    }
+
+    /// Pops with `pop` until it gets something, the deque is closed or `deadline` passes.
+    fn pop_waiting(&self, deadline: Option<Instant>, pop: fn(&mut DoublyLinkedList<T>) -> Option<T>) -> Option<T> {
+        let mut state = self.lock();
+        loop {
+            if let Some(value) = pop(&mut state.list) {
+                return Some(value);
+            }
+            if state.closed {
+                return None;
+            }
+
+            let timeout = match deadline {
+                None => None,
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(left) if !left.is_zero() => Some(left),
+                    _ => return None,
+                },
+            };
+            state.waiting += 1;
+            state = match timeout {
+                None => self.not_empty.wait(state).unwrap(),
+                Some(left) => self.not_empty.wait_timeout(state, left).unwrap().0,
+            };
+            state.waiting -= 1;
+        }
+    }
+
+    /// Wakes every blocked pop, and stops later ones from waiting on an empty
+    /// deque. Pushes still work, and `reopen` undoes it.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+    }
+
+    pub fn reopen(&self) {
+        self.lock().closed = false;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().list.is_empty()
+    }
+
+    /// Empties the deque, returning everything that was in it.
+    pub fn take_all(&self) -> DoublyLinkedList<T> {
+        mem::take(&mut self.lock().list)
+    }
+
+    pub fn into_inner(self) -> DoublyLinkedList<T> {
+        self.state.into_inner().unwrap().list
+    }
+}
+
+impl<T> Default for ConcurrentDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<DoublyLinkedList<T>> for ConcurrentDeque<T> {
+    fn from(list: DoublyLinkedList<T>) -> Self {
+        ConcurrentDeque {
+            state: Mutex::new(State {
+                list,
+                closed: false,
+                waiting: 0,
+            }),
+            not_empty: Condvar::new(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ConcurrentDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.lock().list.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_deque_works() {
+        let d = ConcurrentDeque::new();
+        d.push_back(2);
+        d.push_front(1);
+        d.push_back(3);
+        assert_eq!(d.len(), 3);
+        assert_eq!(format!("{d:?}"), "[1, 2, 3]");
+        assert_eq!(d.pop_back(), Some(3));
+        assert_eq!(d.pop_front(), Some(1));
+        assert_eq!(d.pop_front_timeout(Duration::ZERO), Some(2));
+        assert_eq!(d.pop_back(), None);
+        assert!(d.is_empty());
+
+        d.push_back(4);
+        assert_eq!(d.take_all().pop_front(), Some(4));
+        d.push_back(5);
+        assert_eq!(d.into_inner().pop_front(), Some(5));
+    }
+
+    #[test]
+    fn concurrent_deque_is_send_and_sync() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<ConcurrentDeque<Vec<u8>>>();
+    }
+
+    #[test]
+    fn timed_pop_gives_up() {
+        let d = ConcurrentDeque::<u8>::new();
+        let start = Instant::now();
+        assert_eq!(d.pop_back_timeout(Duration::from_millis(20)), None);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn blocked_pop_wakes_on_push_and_close() {
+        let d = Arc::new(ConcurrentDeque::new());
+        let popper = {
+            let d = Arc::clone(&d);
+            thread::spawn(move || (d.pop_front_blocking(), d.pop_back_blocking()))
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        d.push_back(7);
+        thread::sleep(Duration::from_millis(20));
+        d.close();
+        assert_eq!(popper.join().unwrap(), (Some(7), None));
+
+        // closed only stops pops from waiting, it doesn't stop them from popping
+        assert!(d.is_closed());
+        d.push_front(8);
+        assert_eq!(d.pop_back_blocking(), Some(8));
+        d.reopen();
+        assert_eq!(d.pop_back_timeout(Duration::from_millis(1)), None);
+    }
+
+    #[test]
+    fn stress_both_ends() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+
+        let d = Arc::new(ConcurrentDeque::new());
+
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let d = Arc::clone(&d);
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        let v = p * PER_PRODUCER + i;
+                        if p % 2 == 0 {
+                            d.push_back(v);
+                        } else {
+                            d.push_front(v);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let consumers = (0..CONSUMERS)
+            .map(|c| {
+                let d = Arc::clone(&d);
+                thread::spawn(move || {
+                    let mut got = vec![];
+                    loop {
+                        let v = match c % 3 {
+                            0 => d.pop_front_blocking(),
+                            1 => d.pop_back_blocking(),
+                            // mix in the non-blocking pops too, falling back to a wait
+                            _ => d.pop_front().or_else(|| d.pop_back_timeout(Duration::from_millis(1))),
+                        };
+                        match v {
+                            Some(v) => got.push(v),
+                            None if d.is_closed() && d.is_empty() => break,
+                            None => {}
+                        }
+                    }
+                    got
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for p in producers {
+            p.join().unwrap();
+        }
+        d.close();
+
+        let mut all = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect::<Vec<_>>();
+        all.sort_unstable();
+        assert_eq!(all, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn stress_keeps_each_producers_order() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+
+        let d = Arc::new(ConcurrentDeque::new());
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let d = Arc::clone(&d);
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        d.push_back(p * PER_PRODUCER + i);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // a single consumer at the front sees each producer's values in the order they were pushed
+        let mut last = [None; PRODUCERS];
+        for _ in 0..PRODUCERS * PER_PRODUCER {
+            let v = d.pop_front_blocking().unwrap();
+            let p = v / PER_PRODUCER;
+            assert!(last[p] < Some(v));
+            last[p] = Some(v);
+        }
+        for p in producers {
+            p.join().unwrap();
+        }
+        assert!(d.is_empty());
+    }
+}
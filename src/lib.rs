@@ -1,8 +1,14 @@
 pub mod array_list;
+pub mod cache;
 pub mod doubly_linked_list;
 pub mod linked_list;
+pub mod monotonic;
 pub mod queue;
 pub mod rawvec;
 pub mod ring_buffer;
 pub mod search;
 pub mod stack;
+pub mod traits;
+
+#[cfg(test)]
+mod test_util;
@@ -1,81 +1,693 @@
 #![allow(dead_code)]
 
-#[derive(Debug, PartialEq)]
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
 pub struct LinkedList<T> {
     len: usize,
-    head: Option<Box<Node<T>>>,
+    head: Link<T>,
+    // the last node, so the back can be reached without walking the list
+    tail: Link<T>,
+    // the list owns its nodes, as if they were boxed
+    marker: PhantomData<Box<Node<T>>>,
 }
 
-#[derive(Debug, PartialEq)]
+type Link<T> = Option<NonNull<Node<T>>>;
+
 struct Node<T> {
     value: T,
-    next: Option<Box<Node<T>>>,
+    next: Link<T>,
 }
 
 impl<T> Node<T> {
     fn new(value: T) -> Self {
         Node { value, next: None }
     }
+
+    fn alloc(value: T) -> NonNull<Self> {
+        NonNull::from(Box::leak(Box::new(Node::new(value))))
+    }
 }
 
+// the list hands out its values like a `Box` would
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
-        LinkedList { len: 0, head: None }
+        LinkedList {
+            len: 0,
+            head: None,
+            tail: None,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn push_front(&mut self, value: T) {
+        let n = Node::alloc(value);
+        unsafe { (*n.as_ptr()).next = self.head };
         if self.head.is_none() {
-            self.head = Some(Box::new(Node::new(value)));
-        } else {
-            let mut n = Node::new(value);
-            n.next = self.head.take();
-            self.head = Some(Box::new(n));
+            self.tail = Some(n);
+        }
+        self.head = Some(n);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let n = Node::alloc(value);
+        match self.tail {
+            Some(t) => unsafe { (*t.as_ptr()).next = Some(n) },
+            None => self.head = Some(n),
         }
+        self.tail = Some(n);
         self.len += 1;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
-        if let Some(mut h) = self.head.take() {
-            self.head = h.next.take();
+        self.head.map(|h| {
+            // the node came from `Node::alloc` and is unlinked right away
+            let h = unsafe { Box::from_raw(h.as_ptr()) };
+            self.head = h.next;
+            if self.head.is_none() {
+                self.tail = None;
+            }
             self.len -= 1;
-            Some(h.value)
-        } else {
-            None
-        }
+            h.value
+        })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|n| unsafe { &(*n.as_ptr()).value })
     }
 
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|n| unsafe { &mut (*n.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|n| unsafe { &(*n.as_ptr()).value })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|n| unsafe { &mut (*n.as_ptr()).value })
+    }
+
+    /// Splits off everything after the first `at` elements, or returns `None` if
+    /// there aren't that many or `at` is 0.
     pub fn split_off(&mut self, at: usize) -> Option<Self> {
-        let mut next = &mut self.head;
-        let mut i = 0;
+        if at == 0 || at > self.len {
+            return None;
+        }
+
+        let mut cursor = self.cursor_front_mut();
+        for _ in 0..at {
+            cursor.move_next();
+        }
+        Some(cursor.split_after())
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// A cursor positioned before the first element.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            list: self,
+            prev: None,
+            index: 0,
+        }
+    }
+
+    /// Moves every element of `l` to the back of this list.
+    pub fn append(&mut self, mut l: Self) {
+        let (Some(head), Some(tail)) = (l.head.take(), l.tail.take()) else {
+            return;
+        };
+
+        match self.tail {
+            Some(t) => unsafe { (*t.as_ptr()).next = Some(head) },
+            None => self.head = Some(head),
+        }
+        self.tail = Some(tail);
+        self.len += l.len;
+        l.len = 0;
+    }
+}
+
+/// In-place algorithms. None of them allocate: they only relink existing nodes.
+impl<T> LinkedList<T> {
+    pub fn reverse(&mut self) {
+        let mut prev = None;
+        let mut next = self.head;
+        self.tail = self.head;
+
         while let Some(n) = next {
-            i += 1;
-            if i == at {
-                let mut l = LinkedList::new();
-                l.head = n.next.take();
-                l.len = self.len - i;
-                self.len -= l.len;
-                return Some(l);
+            unsafe {
+                next = (*n.as_ptr()).next;
+                (*n.as_ptr()).next = prev;
+            }
+            prev = Some(n);
+        }
+        self.head = prev;
+    }
+
+    /// Keeps only the elements for which `f` returns true, preserving their order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut c = self.cursor_front_mut();
+        while let Some(v) = c.peek_next() {
+            if f(v) {
+                c.move_next();
+            } else {
+                c.remove_next();
+            }
+        }
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)` returns true.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        let Some(mut prev) = self.head else {
+            return;
+        };
+
+        unsafe {
+            while let Some(n) = (*prev.as_ptr()).next {
+                if same_bucket(&mut (*n.as_ptr()).value, &mut (*prev.as_ptr()).value) {
+                    // unlinked before its value drops, so a panicking drop leaves a valid list
+                    let n = Box::from_raw(n.as_ptr());
+                    (*prev.as_ptr()).next = n.next;
+                    if n.next.is_none() {
+                        self.tail = Some(prev);
+                    }
+                    self.len -= 1;
+                } else {
+                    prev = n;
+                }
+            }
+        }
+    }
+
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// The `n`th element counting back from the last one, which is 0.
+    pub fn nth_from_end(&self, n: usize) -> Option<&T> {
+        let i = self.len.checked_sub(n)?.checked_sub(1)?;
+        self.iter().nth(i)
+    }
+
+    /// The middle element, or the second of the two middle ones for an even length.
+    pub fn middle(&self) -> Option<&T> {
+        self.iter().nth(self.len / 2)
+    }
+
+    /// Rotates the list `n` places to the left, so the element at `n` becomes the front.
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotating by {n} is out of bounds for a length of {}", self.len);
+
+        if n == 0 || n == self.len {
+            return;
+        }
+
+        // the new tail is the node just before the new front
+        let (new_head, new_tail) = cut_after(self.head, n);
+        let Some(old_tail) = self.tail else {
+            unreachable!("a list with 0 < n < len elements has a tail");
+        };
+        unsafe { (*old_tail.as_ptr()).next = self.head };
+        self.head = new_head;
+        self.tail = new_tail;
+    }
+
+    /// Rotates the list `n` places to the right, so the front element ends up at `n`.
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotating by {n} is out of bounds for a length of {}", self.len);
+
+        self.rotate_left(self.len - n);
+    }
+
+    /// Sorts the list, keeping equal elements in order. A bottom-up merge
+    /// sort, so it takes O(n log n) time and no extra space.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let mut width = 1;
+        while width < self.len {
+            let rest = self.head.take();
+            let mut pass = Merge::new(self, rest);
+
+            while pass.rest.is_some() {
+                pass.a = pass.rest;
+                pass.b = cut_after(pass.a, width).0;
+                pass.rest = cut_after(pass.b, width).0;
+                pass.merge(&mut compare);
+            }
+
+            width = width.saturating_mul(2);
+        }
+    }
+
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        self.sort_by(|a, b| key(a).cmp(&key(b)))
+    }
+
+    /// Merges `other` into this list, assuming both are sorted by `compare`.
+    /// On ties, this list's elements come first.
+    pub fn merge_sorted_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut other: Self, mut compare: F) {
+        self.len += other.len;
+        other.len = 0;
+        other.tail = None;
+
+        let a = self.head.take();
+        let mut pass = Merge::new(self, None);
+        pass.a = a;
+        pass.b = other.head.take();
+        pass.merge(&mut compare);
+    }
+}
+
+impl<T: PartialEq> LinkedList<T> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+impl<T: Ord> LinkedList<T> {
+    pub fn sort(&mut self) {
+        self.sort_by(|a, b| a.cmp(b))
+    }
+
+    /// Merges `other` into this list, assuming both are sorted.
+    pub fn merge_sorted(&mut self, other: Self) {
+        self.merge_sorted_by(other, |a, b| a.cmp(b))
+    }
+}
+
+/// Cuts the chain starting at `start` after `n` nodes, returning the rest of
+/// the chain and the last node kept, if there were any.
+fn cut_after<T>(start: Link<T>, n: usize) -> (Link<T>, Link<T>) {
+    let mut last = None;
+    let mut next = start;
+    for _ in 0..n {
+        let Some(node) = next else {
+            break;
+        };
+        last = Some(node);
+        next = unsafe { (*node.as_ptr()).next };
+    }
+
+    if let Some(last) = last {
+        unsafe { (*last.as_ptr()).next = None };
+    }
+    (next, last)
+}
+
+/**
+One merge pass over a list's nodes, split into the merged output so far, the
+two sorted runs being merged and whatever's left. Dropping it stitches the
+pieces back into the list in that order, so a panicking comparison still
+leaves every node in the list.
+*/
+struct Merge<'a, T> {
+    list: &'a mut LinkedList<T>,
+    done_head: Link<T>,
+    done_tail: Link<T>,
+    a: Link<T>,
+    b: Link<T>,
+    rest: Link<T>,
+}
+
+impl<'a, T> Merge<'a, T> {
+    fn new(list: &'a mut LinkedList<T>, rest: Link<T>) -> Self {
+        Merge {
+            list,
+            done_head: None,
+            done_tail: None,
+            a: None,
+            b: None,
+            rest,
+        }
+    }
+
+    /// Moves nodes from the fronts of `a` and `b` to the output until both are empty.
+    fn merge<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: &mut F) {
+        loop {
+            let take_b = match (self.a, self.b) {
+                // only take from `b` when it's strictly smaller, to stay stable
+                (Some(a), Some(b)) => unsafe {
+                    compare(&(*b.as_ptr()).value, &(*a.as_ptr()).value) == Ordering::Less
+                },
+                (Some(_), None) => false,
+                (None, Some(_)) => true,
+                (None, None) => return,
+            };
+
+            let run = if take_b { &mut self.b } else { &mut self.a };
+            let Some(n) = *run else {
+                unreachable!("the picked run isn't empty");
+            };
+            unsafe {
+                *run = (*n.as_ptr()).next;
+                (*n.as_ptr()).next = None;
+                match self.done_tail {
+                    Some(t) => (*t.as_ptr()).next = Some(n),
+                    None => self.done_head = Some(n),
+                }
             }
-            next = &mut n.next;
+            self.done_tail = Some(n);
         }
-        None
     }
+}
 
-    pub fn append(&mut self, l: Self) {
-        let mut node = &mut self.head;
-        while let Some(n) = node {
-            node = &mut n.next;
+impl<T> Drop for Merge<'_, T> {
+    fn drop(&mut self) {
+        let mut head = self.done_head;
+        let mut tail = self.done_tail;
+
+        for chain in [self.a.take(), self.b.take(), self.rest.take()] {
+            let Some(first) = chain else {
+                continue;
+            };
+
+            unsafe {
+                match tail {
+                    Some(t) => (*t.as_ptr()).next = Some(first),
+                    None => head = Some(first),
+                }
+                let mut last = first;
+                while let Some(n) = (*last.as_ptr()).next {
+                    last = n;
+                }
+                tail = Some(last);
+            }
         }
-        if let Some(h) = l.head {
-            node.replace(h);
-            self.len += l.len;
+
+        self.list.head = head;
+        self.list.tail = tail;
+    }
+}
+
+/// Frees one node at a time, so a long list can't blow the stack the way
+/// nested `Box` drops would.
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for LinkedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for LinkedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for LinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        self.iter().for_each(|v| v.hash(state));
+    }
+}
+
+/// Adds the items to the back of the list, in order.
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.push_back(v);
         }
     }
 }
 
+impl<'a, T: Copy + 'a> Extend<&'a T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut l = LinkedList::new();
+        l.extend(iter);
+        l
+    }
+}
+
+impl<T> From<Vec<T>> for LinkedList<T> {
+    fn from(v: Vec<T>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for LinkedList<T> {
+    fn from(arr: [T; N]) -> Self {
+        arr.into_iter().collect()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|n| unsafe {
+            let n = &*n.as_ptr();
+            self.next = n.next;
+            self.len -= 1;
+            &n.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    next: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|n| unsafe {
+            // only the value is handed out, `next` stays free to read
+            let n = n.as_ptr();
+            self.next = (*n).next;
+            self.len -= 1;
+            &mut (*n).value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+// the iterators stand in for `&T` and `&mut T`
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+/**
+Edits a list in place. The cursor sits between two elements, starting before
+the first one, and every edit happens right after it, so none of them have to
+walk the list.
+*/
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    // the node before the cursor, `None` at the front
+    prev: Link<T>,
+    index: usize,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    fn next_node(&self) -> Link<T> {
+        match self.prev {
+            Some(p) => unsafe { (*p.as_ptr()).next },
+            None => self.list.head,
+        }
+    }
+
+    fn set_next_node(&mut self, n: Link<T>) {
+        match self.prev {
+            Some(p) => unsafe { (*p.as_ptr()).next = n },
+            None => self.list.head = n,
+        }
+    }
+
+    /// How many elements are before the cursor.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The element right after the cursor.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_node().map(|n| unsafe { &mut (*n.as_ptr()).value })
+    }
+
+    /// Steps over the next element, returning `false` if the cursor is already at the end.
+    pub fn move_next(&mut self) -> bool {
+        match self.next_node() {
+            Some(n) => {
+                self.prev = Some(n);
+                self.index += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts `value` right after the cursor, so it's the next element.
+    pub fn insert_after(&mut self, value: T) {
+        let n = Node::alloc(value);
+        let next = self.next_node();
+        unsafe { (*n.as_ptr()).next = next };
+        self.set_next_node(Some(n));
+        if next.is_none() {
+            self.list.tail = Some(n);
+        }
+        self.list.len += 1;
+    }
+
+    /// Removes the element right after the cursor.
+    pub fn remove_next(&mut self) -> Option<T> {
+        let n = self.next_node()?;
+        // the node came from `Node::alloc` and is unlinked right away
+        let n = unsafe { Box::from_raw(n.as_ptr()) };
+        self.set_next_node(n.next);
+        if n.next.is_none() {
+            self.list.tail = self.prev;
+        }
+        self.list.len -= 1;
+        Some(n.value)
+    }
+
+    /// Cuts the list at the cursor, returning everything after it.
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        let Some(head) = self.next_node() else {
+            return LinkedList::new();
+        };
+
+        let rest = LinkedList {
+            len: self.list.len - self.index,
+            head: Some(head),
+            tail: self.list.tail,
+            marker: PhantomData,
+        };
+        self.set_next_node(None);
+        self.list.tail = self.prev;
+        self.list.len = self.index;
+        rest
+    }
+
+    /// Moves all of `other` in right after the cursor, which stays before
+    /// the first spliced element. This is O(1): both lists know their tails,
+    /// so neither is walked.
+    pub fn splice_after(&mut self, mut other: LinkedList<T>) {
+        let (Some(head), Some(tail)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+
+        let next = self.next_node();
+        unsafe { (*tail.as_ptr()).next = next };
+        self.set_next_node(Some(head));
+        if next.is_none() {
+            self.list.tail = Some(tail);
+        }
+        self.list.len += other.len;
+        other.len = 0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::rng;
 
     #[test]
     fn linked_list_works() {
@@ -116,4 +728,329 @@ mod tests {
         assert_eq!(split.pop_front(), Some(6));
         assert_eq!(split.pop_front(), Some(5));
     }
+
+    #[test]
+    fn linked_list_iter_works() {
+        let mut l = LinkedList::<usize>::new();
+        assert_eq!(l.iter().next(), None);
+        l.push_front(3);
+        l.push_front(2);
+        l.push_front(1);
+        assert_eq!(l.iter().len(), 3);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        for v in &mut l {
+            *v *= 2;
+        }
+        assert_eq!((&l).into_iter().sum::<usize>(), 12);
+        let mut it = l.iter_mut();
+        it.next();
+        assert_eq!(it.len(), 2);
+        assert_eq!(l.pop_front(), Some(2));
+    }
+
+    #[test]
+    fn cursor_filters_in_place() {
+        let mut l = LinkedList::from([1, 2, 3, 4, 5, 6]);
+        let mut c = l.cursor_front_mut();
+        while let Some(v) = c.peek_next() {
+            if *v % 2 == 1 {
+                c.remove_next();
+            } else {
+                *v *= 10;
+                c.move_next();
+            }
+        }
+        assert!(!c.move_next());
+        assert_eq!(c.index(), 3);
+        c.insert_after(70);
+        assert_eq!(l.len(), 4);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![20, 40, 60, 70]);
+    }
+
+    #[test]
+    fn cursor_splits_and_splices() {
+        let mut l = LinkedList::from([1, 2, 5, 6]);
+        let mut c = l.cursor_front_mut();
+        assert_eq!(c.remove_next(), Some(1));
+        c.insert_after(1);
+        c.move_next();
+        c.move_next();
+        c.splice_after(LinkedList::from([3, 4]));
+        c.splice_after(LinkedList::new());
+        assert_eq!(c.peek_next(), Some(&mut 3));
+
+        c.move_next();
+        let rest = c.split_after();
+        assert_eq!(rest.len(), 3);
+        assert_eq!(rest.iter().copied().collect::<Vec<_>>(), vec![4, 5, 6]);
+        assert_eq!(c.remove_next(), None);
+        assert_eq!(l.len(), 3);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn cursor_merges_sorted_lists() {
+        let mut a = LinkedList::from([1, 4, 6, 9]);
+        let mut b = LinkedList::from([2, 3, 7, 8, 10, 11]);
+
+        let mut c = a.cursor_front_mut();
+        while let Some(v) = b.pop_front() {
+            while c.peek_next().is_some_and(|n| *n < v) {
+                c.move_next();
+            }
+            c.insert_after(v);
+        }
+        assert_eq!(a.len(), 10);
+        assert_eq!(
+            a.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 6, 7, 8, 9, 10, 11]
+        );
+    }
+
+    /// Walks the list and checks `tail` and `len` agree with it.
+    fn assert_consistent<T>(l: &LinkedList<T>) {
+        let mut last = None;
+        let mut next = l.head;
+        let mut len = 0;
+        while let Some(n) = next {
+            last = Some(n);
+            next = unsafe { (*n.as_ptr()).next };
+            len += 1;
+        }
+        assert_eq!(l.tail, last);
+        assert_eq!(l.len, len);
+    }
+
+    #[test]
+    fn back_works() {
+        let mut l = LinkedList::new();
+        assert_eq!(l.back(), None);
+        l.push_back(2);
+        assert_eq!(l.front(), Some(&2));
+        l.push_front(1);
+        l.push_back(3);
+        *l.back_mut().unwrap() *= 10;
+        *l.front_mut().unwrap() *= 10;
+        assert_eq!(l.back(), Some(&30));
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![10, 2, 30]);
+        assert_consistent(&l);
+        while l.pop_front().is_some() {
+            assert_consistent(&l);
+        }
+        l.push_back(4);
+        assert_eq!(l.front(), Some(&4));
+        assert_eq!(l.back(), Some(&4));
+    }
+
+    #[test]
+    fn tail_survives_split_and_append() {
+        let mut l = LinkedList::from([1, 2, 3, 4]);
+        let mut rest = l.split_off(4).unwrap();
+        assert!(rest.is_empty());
+        assert_consistent(&l);
+        assert_consistent(&rest);
+        rest.push_back(5);
+        assert_eq!(rest.back(), Some(&5));
+
+        let mut tail_half = l.split_off(2).unwrap();
+        assert_consistent(&l);
+        assert_consistent(&tail_half);
+        l.push_back(20);
+        tail_half.push_back(40);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1, 2, 20]);
+        assert_eq!(tail_half.iter().copied().collect::<Vec<_>>(), vec![3, 4, 40]);
+
+        l.append(LinkedList::new());
+        assert_consistent(&l);
+        let mut empty = LinkedList::new();
+        empty.append(tail_half);
+        assert_consistent(&empty);
+        empty.push_back(50);
+        l.append(empty);
+        l.append(rest);
+        assert_consistent(&l);
+        l.push_back(6);
+        assert_eq!(
+            l.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 20, 3, 4, 40, 50, 5, 6]
+        );
+        assert_eq!(l.split_off(10), None);
+        assert_eq!(l.split_off(0), None);
+    }
+
+    #[test]
+    fn tail_survives_cursor_edits() {
+        let mut l = LinkedList::from([1, 2, 3]);
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        c.move_next();
+        assert_eq!(c.remove_next(), Some(3));
+        c.insert_after(4);
+        c.move_next();
+        c.splice_after(LinkedList::from([5, 6]));
+        assert_consistent(&l);
+        assert_eq!(l.back(), Some(&6));
+
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        let mut rest = c.split_after();
+        assert_consistent(&l);
+        assert_consistent(&rest);
+        l.push_back(7);
+        rest.push_back(8);
+        assert_eq!(l.iter().copied().collect::<Vec<_>>(), vec![1, 7]);
+        assert_eq!(rest.iter().copied().collect::<Vec<_>>(), vec![2, 4, 5, 6, 8]);
+
+        let mut c = rest.cursor_front_mut();
+        while c.remove_next().is_some() {}
+        assert_consistent(&rest);
+        rest.push_back(9);
+        assert_eq!(rest.front(), Some(&9));
+        assert_eq!(rest.back(), Some(&9));
+    }
+
+    #[test]
+    fn long_lists_dont_overflow_the_stack() {
+        struct Counter(usize);
+
+        impl fmt::Write for Counter {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 += s.len();
+                Ok(())
+            }
+        }
+
+        // miri is far too slow for the full size, but still checks the iterative drop and clone
+        const N: usize = if cfg!(miri) { 1_000 } else { 10_000_000 };
+        let mut l = LinkedList::new();
+        for _ in 0..N {
+            l.push_front(0u8);
+        }
+
+        let copy = l.clone();
+        assert_eq!(copy.len(), N);
+        assert!(copy == l);
+        assert_eq!(copy.cmp(&l), Ordering::Equal);
+
+        let mut out = Counter(0);
+        fmt::write(&mut out, format_args!("{:?}", l)).unwrap();
+        // "[" + "0, " for all but the last + "0]"
+        assert_eq!(out.0, 3 * N);
+
+        drop(copy);
+        drop(l);
+    }
+
+    /// Short lists of small values, so there are plenty of ties and duplicates.
+    fn random_vec(next: &mut impl FnMut() -> usize) -> Vec<(u8, usize)> {
+        let len = next() % 40;
+        (0..len).map(|i| ((next() % 6) as u8, i)).collect()
+    }
+
+    fn items<T: Clone>(l: &LinkedList<T>) -> Vec<T> {
+        l.iter().cloned().collect()
+    }
+
+    #[test]
+    fn algorithms_match_vec() {
+        let mut next = rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..if cfg!(miri) { 20 } else { 500 } {
+            let v = random_vec(&mut next);
+            let l = LinkedList::from(v.clone());
+
+            let mut reversed = l.clone();
+            reversed.reverse();
+            assert_consistent(&reversed);
+            assert_eq!(items(&reversed), v.iter().rev().cloned().collect::<Vec<_>>());
+
+            // sorting by the small key only shows whether ties keep their order
+            let mut sorted = l.clone();
+            sorted.sort_by_key(|p| p.0);
+            let mut model = v.clone();
+            model.sort_by_key(|p| p.0);
+            assert_consistent(&sorted);
+            assert_eq!(items(&sorted), model);
+
+            let mut other = random_vec(&mut next);
+            other.sort_by_key(|p| p.0);
+            let mut merged = sorted;
+            merged.merge_sorted_by(LinkedList::from(other.clone()), |a, b| a.0.cmp(&b.0));
+            model.extend(other);
+            model.sort_by_key(|p| p.0);
+            assert_consistent(&merged);
+            assert_eq!(items(&merged), model);
+
+            let mut deduped = l.clone();
+            deduped.dedup_by_key(|p| p.0);
+            let mut model = v.clone();
+            model.dedup_by_key(|p| p.0);
+            assert_consistent(&deduped);
+            assert_eq!(items(&deduped), model);
+
+            let mut retained = l.clone();
+            retained.retain(|p| p.0 % 2 == 0);
+            let mut model = v.clone();
+            model.retain(|p| p.0 % 2 == 0);
+            assert_consistent(&retained);
+            assert_eq!(items(&retained), model);
+
+            let n = next() % (v.len() + 2);
+            assert_eq!(l.nth_from_end(n), v.iter().rev().nth(n));
+            assert_eq!(l.middle(), v.get(v.len() / 2));
+
+            let n = next() % (v.len() + 1);
+            let mut rotated = l.clone();
+            rotated.rotate_left(n);
+            let mut model = v.clone();
+            model.rotate_left(n);
+            assert_consistent(&rotated);
+            assert_eq!(items(&rotated), model);
+            rotated.rotate_right(n);
+            assert_eq!(items(&rotated), v);
+        }
+    }
+
+    #[test]
+    fn plain_sort_and_merge_work() {
+        let mut a = LinkedList::from([5, 1, 4, 1, 3]);
+        a.sort();
+        let mut b = LinkedList::from([9, 2, 6]);
+        b.sort();
+        a.merge_sorted(b);
+        a.merge_sorted(LinkedList::new());
+        a.dedup();
+        assert_eq!(items(&a), vec![1, 2, 3, 4, 5, 6, 9]);
+        a.push_back(10);
+        assert_eq!(a.back(), Some(&10));
+
+        let mut empty = LinkedList::new();
+        empty.merge_sorted(a);
+        assert_consistent(&empty);
+        assert_eq!(empty.len(), 8);
+    }
+
+    #[test]
+    fn panicking_sort_keeps_every_node() {
+        let mut l = LinkedList::from((0..50).rev().collect::<Vec<_>>());
+        let mut calls = 0;
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            l.sort_by(|a, b| {
+                calls += 1;
+                assert!(calls < 100, "boom");
+                a.cmp(b)
+            })
+        }));
+        assert!(res.is_err());
+        assert_consistent(&l);
+        let mut all = items(&l);
+        all.sort();
+        assert_eq!(all, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn rotate_list_out_of_bounds_panics() {
+        LinkedList::from([1, 2]).rotate_left(3);
+    }
 }
@@ -0,0 +1,503 @@
+/*!
+Stacks and queues that also know their smallest (and largest) element.
+
+`MinStack` keeps a second stack of where each new minimum was pushed, so `min`
+is a lookup. `MonotonicQueue` keeps two deques of positions whose values only
+fall (for the max) or only rise (for the min) from front to back: a push drops
+every position behind it that can never be the answer again, and a pop only
+has to check the front, so both are O(1) amortized. `SlidingWindow` bounds one
+to the last `size` values, which is what `window_maxima` and `window_minima`
+slide over a slice.
+*/
+
+use std::fmt;
+
+use crate::array_list::ArrayList;
+use crate::ring_buffer::RingBuffer;
+
+/// A stack whose smallest element can be read in O(1).
+#[derive(Clone)]
+pub struct MinStack<T> {
+    values: ArrayList<T>,
+    // indices into `values` of each element that was smaller than everything below it
+    mins: ArrayList<usize>,
+}
+
+impl<T: Ord> MinStack<T> {
+    pub fn new() -> Self {
+        MinStack {
+            values: ArrayList::new(),
+            mins: ArrayList::new(),
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        MinStack {
+            values: ArrayList::with_capacity(cap),
+            mins: ArrayList::with_capacity(cap),
+        }
+    }
+
+    pub fn push(&mut self, val: T) {
+        // an equal value doesn't need an entry, the older one outlives it
+        if self.min().is_none_or(|min| val < *min) {
+            self.mins.push(self.values.len());
+        }
+        self.values.push(val);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let val = self.values.pop()?;
+        if self.mins.last() == Some(&self.values.len()) {
+            self.mins.pop();
+        }
+        Some(val)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.values.last()
+    }
+
+    /// The smallest element, the lowest one in the stack on a tie.
+    pub fn min(&self) -> Option<&T> {
+        self.mins.last().map(|&i| &self.values[i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.mins.clear();
+    }
+
+    /// Walks the elements from the top down, in the order `pop` would return them.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        self.values.iter().rev()
+    }
+}
+
+impl<T: Ord> Default for MinStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MinStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.values.iter().rev()).finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for MinStack<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = Self::new();
+        s.extend(iter);
+        s
+    }
+}
+
+/**
+A FIFO queue whose largest and smallest elements can be read in O(1).
+
+Elements are numbered as they're pushed, and the two deques hold those
+numbers rather than the elements, so `T` needn't be `Clone`.
+*/
+#[derive(Clone)]
+pub struct MonotonicQueue<T> {
+    values: RingBuffer<T>,
+    // how many elements have been popped, so element `n` is at `values[n - popped]`
+    popped: usize,
+    // falling from front to back, so the front is the max
+    maxes: RingBuffer<usize>,
+    // rising from front to back, so the front is the min
+    mins: RingBuffer<usize>,
+}
+
+impl<T: Ord> MonotonicQueue<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        MonotonicQueue {
+            values: RingBuffer::growing(cap),
+            popped: 0,
+            maxes: RingBuffer::growing(0),
+            mins: RingBuffer::growing(0),
+        }
+    }
+
+    fn get(&self, n: usize) -> &T {
+        &self.values[n.wrapping_sub(self.popped)]
+    }
+
+    /// Adds `val` at the back.
+    pub fn push(&mut self, val: T) {
+        let n = self.popped.wrapping_add(self.values.len());
+
+        // ties keep the older element, which leaves first
+        while self.maxes.back().is_some_and(|&m| *self.get(m) < val) {
+            self.maxes.pop_back();
+        }
+        while self.mins.back().is_some_and(|&m| *self.get(m) > val) {
+            self.mins.pop_back();
+        }
+        // growing buffers never overwrite or reject, so there's nothing to check
+        let _ = self.maxes.push_back(n);
+        let _ = self.mins.push_back(n);
+        let _ = self.values.push_back(val);
+    }
+
+    /// Removes the element at the front, the oldest one.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let val = self.values.pop_front()?;
+        if self.maxes.front() == Some(&self.popped) {
+            self.maxes.pop_front();
+        }
+        if self.mins.front() == Some(&self.popped) {
+            self.mins.pop_front();
+        }
+        self.popped = self.popped.wrapping_add(1);
+        Some(val)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.values.front()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.values.back()
+    }
+
+    /// The largest element, the oldest one on a tie.
+    pub fn max(&self) -> Option<&T> {
+        self.maxes.front().map(|&n| self.get(n))
+    }
+
+    /// The smallest element, the oldest one on a tie.
+    pub fn min(&self) -> Option<&T> {
+        self.mins.front().map(|&n| self.get(n))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Walks the elements from the front, in the order `pop_front` would return them.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        self.values.iter()
+    }
+}
+
+impl<T: Ord> Default for MonotonicQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MonotonicQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.values.iter()).finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for MonotonicQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MonotonicQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut q = Self::new();
+        q.extend(iter);
+        q
+    }
+}
+
+/// The last `size` values of a stream, with their max and min in O(1).
+#[derive(Clone)]
+pub struct SlidingWindow<T> {
+    queue: MonotonicQueue<T>,
+    size: usize,
+}
+
+impl<T: Ord> SlidingWindow<T> {
+    /**
+    Makes an empty window over the last `size` values.
+
+    # Panics
+    Panics if `size` is 0.
+    */
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "a window needs room for at least one value");
+        SlidingWindow {
+            queue: MonotonicQueue::with_capacity(size),
+            size,
+        }
+    }
+
+    /// Slides the window over `val`, returning the value that fell out of it, if any.
+    pub fn push(&mut self, val: T) -> Option<T> {
+        let old = if self.is_full() {
+            self.queue.pop_front()
+        } else {
+            None
+        };
+        self.queue.push(val);
+        old
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.queue.max()
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.queue.min()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Whether `size` values have been pushed, so every push now slides one out.
+    pub fn is_full(&self) -> bool {
+        self.queue.len() == self.size
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear()
+    }
+
+    /// Walks the values in the window, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        self.queue.iter()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SlidingWindow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlidingWindow")
+            .field("size", &self.size)
+            .field("values", &self.queue)
+            .finish()
+    }
+}
+
+/**
+The max of every run of `size` consecutive values, in order, so there are
+`values.len() - size + 1` of them (or none if `size` is bigger).
+
+# Panics
+Panics if `size` is 0.
+*/
+pub fn window_maxima<T: Ord>(values: &[T], size: usize) -> ArrayList<&T> {
+    slide(values, size, SlidingWindow::max)
+}
+
+/// Like `window_maxima`, but for the min.
+pub fn window_minima<T: Ord>(values: &[T], size: usize) -> ArrayList<&T> {
+    slide(values, size, SlidingWindow::min)
+}
+
+fn slide<'a, T: Ord>(
+    values: &'a [T],
+    size: usize,
+    pick: for<'w> fn(&'w SlidingWindow<&'a T>) -> Option<&'w &'a T>,
+) -> ArrayList<&'a T> {
+    let mut window = SlidingWindow::new(size);
+    let mut out = ArrayList::with_capacity((values.len() + 1).saturating_sub(size));
+    for v in values {
+        window.push(v);
+        if window.is_full() {
+            out.push(*pick(&window).unwrap());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::rng;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn min_stack_works() {
+        let mut s = MinStack::new();
+        assert_eq!(s.min(), None);
+        s.extend([5, 3, 7, 3, 1]);
+        assert_eq!(s.min(), Some(&1));
+        assert_eq!(s.pop(), Some(1));
+        assert_eq!(s.min(), Some(&3));
+        s.pop();
+        // the other 3 is still there
+        assert_eq!(s.min(), Some(&3));
+        assert_eq!(s.peek(), Some(&7));
+        assert_eq!(format!("{s:?}"), "[7, 3, 5]");
+        s.pop();
+        s.pop();
+        assert_eq!(s.min(), Some(&5));
+        s.clear();
+        assert!(s.is_empty());
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn monotonic_queue_works() {
+        let mut q: MonotonicQueue<_> = [4, 1, 4, 2].into_iter().collect();
+        assert_eq!((q.max(), q.min()), (Some(&4), Some(&1)));
+        assert_eq!(q.pop_front(), Some(4));
+        // the second 4 is still there
+        assert_eq!(q.max(), Some(&4));
+        q.pop_front();
+        assert_eq!(q.min(), Some(&2));
+        q.pop_front();
+        assert_eq!((q.max(), q.min()), (Some(&2), Some(&2)));
+        assert_eq!(format!("{q:?}"), "[2]");
+        q.clear();
+        assert_eq!((q.max(), q.min()), (None, None));
+        assert_eq!(q.pop_front(), None);
+    }
+
+    #[test]
+    fn monotonic_queue_keeps_non_clone_values() {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Id(u32);
+
+        let mut q = MonotonicQueue::new();
+        q.push(Id(2));
+        q.push(Id(9));
+        q.push(Id(5));
+        assert_eq!(q.max(), Some(&Id(9)));
+        assert_eq!(q.pop_front(), Some(Id(2)));
+        assert_eq!(q.min(), Some(&Id(5)));
+    }
+
+    #[test]
+    fn sliding_window_works() {
+        let mut w = SlidingWindow::new(3);
+        assert_eq!(w.push(1), None);
+        assert_eq!(w.push(3), None);
+        assert!(!w.is_full());
+        assert_eq!(w.push(2), None);
+        assert!(w.is_full());
+        assert_eq!(w.push(0), Some(1));
+        assert_eq!((w.max(), w.min()), (Some(&3), Some(&0)));
+        assert_eq!(w.push(0), Some(3));
+        assert_eq!(w.max(), Some(&2));
+        assert_eq!(w.iter().copied().collect::<Vec<_>>(), vec![2, 0, 0]);
+        assert_eq!(w.len(), w.size());
+
+        assert_eq!(&window_maxima(&[1, 3, 2, 0, 0], 3)[..], &[&3, &3, &2]);
+        assert_eq!(&window_minima(&[1, 3, 2, 0, 0], 3)[..], &[&1, &0, &0]);
+        assert!(window_maxima(&[1, 2], 3).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_window_panics() {
+        SlidingWindow::<u8>::new(0);
+    }
+
+    #[test]
+    fn min_stack_matches_brute_force() {
+        let mut next = rng(0x853c_49e6_748f_ea9b);
+        let mut s = MinStack::new();
+        let mut model = Vec::new();
+
+        for _ in 0..5000 {
+            // small values, so there are plenty of ties
+            if next().is_multiple_of(3) {
+                assert_eq!(s.pop(), model.pop());
+            } else {
+                let v = next() % 20;
+                s.push(v);
+                model.push(v);
+            }
+            assert_eq!(s.min(), model.iter().min());
+            assert_eq!(s.peek(), model.last());
+            assert_eq!(s.len(), model.len());
+        }
+    }
+
+    #[test]
+    fn monotonic_queue_matches_brute_force() {
+        let mut next = rng(0xda94_2042_e4dd_58b5);
+        let mut q = MonotonicQueue::new();
+        let mut model = VecDeque::new();
+
+        for _ in 0..5000 {
+            if next().is_multiple_of(3) {
+                assert_eq!(q.pop_front(), model.pop_front());
+            } else {
+                let v = next() % 20;
+                q.push(v);
+                model.push_back(v);
+            }
+            assert_eq!(q.max(), model.iter().max());
+            assert_eq!(q.min(), model.iter().min());
+            assert!(q.iter().eq(model.iter()));
+        }
+    }
+
+    #[test]
+    fn monotonic_queue_survives_the_counter_wrapping() {
+        let mut q = MonotonicQueue::new();
+        q.popped = usize::MAX - 2;
+        for i in 0..10 {
+            q.push(i % 4);
+            if i > 3 {
+                q.pop_front();
+            }
+        }
+        assert_eq!(q.iter().copied().collect::<Vec<_>>(), vec![2, 3, 0, 1]);
+        assert_eq!((q.max(), q.min()), (Some(&3), Some(&0)));
+    }
+
+    #[test]
+    fn windows_match_brute_force() {
+        let mut next = rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..200 {
+            let values = (0..next() % 40).map(|_| next() % 10).collect::<Vec<_>>();
+            let size = 1 + next() % 8;
+
+            let maxes = values.windows(size).map(|w| w.iter().max().unwrap()).collect::<Vec<_>>();
+            let mins = values.windows(size).map(|w| w.iter().min().unwrap()).collect::<Vec<_>>();
+            assert_eq!(&window_maxima(&values, size)[..], &maxes[..]);
+            assert_eq!(&window_minima(&values, size)[..], &mins[..]);
+        }
+    }
+}
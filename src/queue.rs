@@ -1,89 +1,199 @@
 #![allow(dead_code)]
 
-use std::collections::{LinkedList, VecDeque};
+use std::collections::{linked_list, vec_deque, LinkedList, VecDeque};
+use std::iter;
 
-use crate::array_list::ArrayList;
-use crate::doubly_linked_list::DoublyLinkedList;
+use crate::doubly_linked_list::{self, DoublyLinkedList};
+use crate::ring_buffer::{self, RingBuffer};
+use crate::traits;
+
+traits::adapter! {
+    /**
+    A collection a `Queue` can keep its elements in. `push` adds to one end, and
+    `pop` and `peek` work on the other.
+    */
+    backend QueueBackend;
+    /// A queue on top of any `QueueBackend`. The aliases name the ones the crate ships with.
+    adapter GenericQueue: Queue;
+    /// `for_each_queue!(m!(args..))` is `m!(args.., [ArrayQueue, ..])` for every alias
+    /// below, so the tests and benches that use it cover each one.
+    aliases for_each_queue {
+        ArrayQueue<T> = RingBuffer<T>,
+        LinkedQueue<T> = DoublyLinkedList<T>,
+        StdArrayQueue<T> = VecDeque<T>,
+        StdLinkedQueue<T> = LinkedList<T>,
+    }
+}
 
 /* implementations based on crate's collections */
 
-pub struct ArrayQueue<T> {
-    list: ArrayList<T>
-}
+/// Pushes at the back and pops at the front, both amortized O(1).
+impl<T> QueueBackend for RingBuffer<T> {
+    type Item = T;
+    type Iter<'a> = ring_buffer::Iter<'a, T>
+    where
+        T: 'a;
 
-impl<T> ArrayQueue<T> {
-    pub fn new() -> Self {
-        ArrayQueue { list: ArrayList::<T>::new() }
+    fn with_capacity(cap: usize) -> Self {
+        RingBuffer::growing(cap)
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push_front(val)
+    fn push(&mut self, val: T) {
+        // `Queue` only makes buffers that grow rather than overwrite
+        let pushed = self.push_back(val);
+        debug_assert!(matches!(pushed, Ok(None)), "a queue's buffer must grow when full");
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop()
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
     }
-}
 
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
 
-pub struct LinkedQueue<T> {
-    list: DoublyLinkedList<T>
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.front_mut()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        RingBuffer::iter(self)
+    }
+
+    fn len(&self) -> usize {
+        RingBuffer::len(self)
+    }
+
+    fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
 }
 
-impl<T> LinkedQueue<T> {
-    pub fn new() -> Self {
-        LinkedQueue { list: DoublyLinkedList::<T>::new() }
+impl<T> QueueBackend for DoublyLinkedList<T> {
+    type Item = T;
+    type Iter<'a> = iter::Rev<doubly_linked_list::Iter<'a, T>>
+    where
+        T: 'a;
+
+    // nodes are allocated one at a time, so there's nothing to reserve
+    fn with_capacity(_cap: usize) -> Self {
+        DoublyLinkedList::new()
+    }
+
+    fn push(&mut self, val: T) {
+        self.push_front(val);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.back_mut()
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push_front(val)
+    fn iter(&self) -> Self::Iter<'_> {
+        DoublyLinkedList::iter(self).rev()
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop_back()
+    fn len(&self) -> usize {
+        DoublyLinkedList::len(self)
+    }
+
+    fn clear(&mut self) {
+        *self = DoublyLinkedList::new()
     }
 }
 
 /* implementations based on stdlib collections */
 
-pub struct StdArrayQueue<T> {
-    list: VecDeque<T>
-}
+impl<T> QueueBackend for VecDeque<T> {
+    type Item = T;
+    type Iter<'a> = iter::Rev<vec_deque::Iter<'a, T>>
+    where
+        T: 'a;
 
-impl<T> StdArrayQueue<T> {
-    pub fn new() -> Self {
-        StdArrayQueue { list: VecDeque::<T>::new() }
+    fn with_capacity(cap: usize) -> Self {
+        VecDeque::with_capacity(cap)
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push_front(val)
+    fn push(&mut self, val: T) {
+        self.push_front(val)
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop_back()
+    fn pop(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.back_mut()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        VecDeque::iter(self).rev()
     }
-}
 
-pub struct StdLinkedQueue<T> {
-    list: LinkedList<T>
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn clear(&mut self) {
+        VecDeque::clear(self)
+    }
 }
 
-impl<T> StdLinkedQueue<T> {
-    pub fn new() -> Self {
-        StdLinkedQueue { list: LinkedList::<T>::new() }
+impl<T> QueueBackend for LinkedList<T> {
+    type Item = T;
+    type Iter<'a> = iter::Rev<linked_list::Iter<'a, T>>
+    where
+        T: 'a;
+
+    // nodes are allocated one at a time, so there's nothing to reserve
+    fn with_capacity(_cap: usize) -> Self {
+        LinkedList::new()
+    }
+
+    fn push(&mut self, val: T) {
+        self.push_front(val)
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push_front(val)
+    fn pop(&mut self) -> Option<T> {
+        self.pop_back()
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop_back()
+    fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.back_mut()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        LinkedList::iter(self).rev()
+    }
+
+    fn len(&self) -> usize {
+        LinkedList::len(self)
+    }
+
+    fn clear(&mut self) {
+        LinkedList::clear(self)
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
 
     #[test]
     fn array_queue_works() {
@@ -95,6 +205,26 @@ mod tests {
         assert_eq!(s.pop(), Some(1));
     }
 
+    #[test]
+    fn array_queue_wraps_and_grows() {
+        let mut q = ArrayQueue::new();
+        let mut model = VecDeque::new();
+        // pop a little less than is pushed, so the contents wrap around while the buffer grows
+        for i in 0..1000 {
+            q.push(i);
+            model.push_back(i);
+            if i % 3 == 0 {
+                assert_eq!(q.pop(), model.pop_front());
+            }
+            assert_eq!(q.peek(), model.front());
+        }
+        assert_eq!(q.len(), model.len());
+        while let Some(v) = q.pop() {
+            assert_eq!(Some(v), model.pop_front());
+        }
+        assert!(model.is_empty());
+    }
+
     #[test]
     fn linked_queue_works() {
         let mut s = LinkedQueue::<usize>::new();
@@ -124,4 +254,6 @@ mod tests {
         assert_eq!(s.pop(), Some(3478));
         assert_eq!(s.pop(), Some(1));
     }
+
+    for_each_queue!(test_util::adapter_tests!(Queue, pops: [1, 2, 3]));
 }
@@ -1,76 +1,434 @@
 #![allow(dead_code)]
 
-use std::alloc::{alloc, dealloc, realloc, Layout};
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::mem;
 use std::ptr::NonNull;
 
-pub struct RawVec<T> {
+/// The error type for `try_*` methods that grow a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity exceeds `isize::MAX` bytes or overflows `usize`.
+    CapacityOverflow,
+    /// The allocator returned an error for the given layout.
+    AllocError { layout: Layout },
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => {
+                write!(f, "memory allocation failed because the computed capacity exceeded the collection's maximum")
+            }
+            TryReserveError::AllocError { layout } => {
+                write!(f, "memory allocation of {} bytes failed", layout.size())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TryReserveError {}
+
+/// The error returned by an `Allocator` that couldn't satisfy a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+/**
+A stable stand-in for the unstable `std::alloc::Allocator` trait.
+
+# Safety
+Memory returned by `allocate`/`grow` must stay valid until it's passed back to
+`deallocate`/`grow` on the same allocator (or a clone of it).
+*/
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    /// `ptr` must have been allocated by this allocator with `layout`.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// # Safety
+    /// `ptr` must have been allocated by this allocator with `old_layout`, and
+    /// `new_layout.size()` must be at least `old_layout.size()`.
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new_ptr = self.allocate(new_layout)?;
+        std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+
+    /// # Safety
+    /// `ptr` must have been allocated by this allocator with `old_layout`, and
+    /// `new_layout.size()` must be at most `old_layout.size()`.
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let new_ptr = self.allocate(new_layout)?;
+        std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), new_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+/// The global allocator, the default for every collection in the crate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = unsafe { alloc::alloc(layout) };
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = alloc::realloc(ptr.as_ptr(), old_layout, new_layout.size());
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = alloc::realloc(ptr.as_ptr(), old_layout, new_layout.size());
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        (**self).grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        (**self).shrink(ptr, old_layout, new_layout)
+    }
+}
+
+/// The capacity `Doubling` and `OneAndHalf` jump to from an empty buffer.
+const MIN_NON_ZERO_CAP: usize = 5;
+
+/**
+Decides how much a `RawVec` grows when it runs out of room.
+
+`next_capacity` gets the current capacity, the capacity that's actually
+needed and the element size in bytes. The `RawVec` never grows to less than
+`required`, so a policy can't stall growth by returning something too small.
+Any `Fn(usize, usize) -> usize` closure taking `(cap, required)` is a policy too.
+*/
+pub trait GrowthPolicy {
+    fn next_capacity(&self, cap: usize, required: usize, elem_size: usize) -> usize;
+}
+
+/// Doubles the capacity, starting at 5. The default policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn next_capacity(&self, cap: usize, required: usize, _elem_size: usize) -> usize {
+        cap.saturating_mul(2).max(required).max(MIN_NON_ZERO_CAP)
+    }
+}
+
+/// Grows the capacity by half, trading more reallocations for less slack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OneAndHalf;
+
+impl GrowthPolicy for OneAndHalf {
+    fn next_capacity(&self, cap: usize, required: usize, _elem_size: usize) -> usize {
+        cap.saturating_add(cap / 2).max(required).max(MIN_NON_ZERO_CAP)
+    }
+}
+
+/// Grows the capacity by a fixed number of elements, bounding over-allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedIncrement(pub usize);
+
+impl GrowthPolicy for FixedIncrement {
+    fn next_capacity(&self, cap: usize, required: usize, _elem_size: usize) -> usize {
+        cap.saturating_add(self.0).max(required)
+    }
+}
+
+/// Doubles the capacity, then rounds the allocation up to a whole number of pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageAligned(pub usize);
+
+impl Default for PageAligned {
+    fn default() -> Self {
+        PageAligned(4096)
+    }
+}
+
+impl GrowthPolicy for PageAligned {
+    fn next_capacity(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        let cap = Doubling.next_capacity(cap, required, elem_size);
+        if elem_size == 0 || self.0 == 0 {
+            return cap;
+        }
+
+        let bytes = cap.saturating_mul(elem_size);
+        let pages = bytes.div_ceil(self.0);
+        pages.saturating_mul(self.0) / elem_size
+    }
+}
+
+impl<F: Fn(usize, usize) -> usize> GrowthPolicy for F {
+    fn next_capacity(&self, cap: usize, required: usize, _elem_size: usize) -> usize {
+        self(cap, required)
+    }
+}
+
+pub struct RawVec<T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
     pub ptr: NonNull<T>,
     cap: usize,
+    alloc: A,
+    growth: G,
 }
 
 impl<T> RawVec<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    pub fn try_with_capacity(cap: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+}
+
+impl<T> Default for RawVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: Allocator> RawVec<T, A> {
+    /// Zero-sized types never allocate, so their capacity is unbounded from the start.
+    pub fn new_in(alloc: A) -> Self {
         RawVec {
             ptr: NonNull::dangling(),
-            cap: 0,
+            cap: if mem::size_of::<T>() == 0 { usize::MAX } else { 0 },
+            alloc,
+            growth: Doubling,
         }
     }
 
-    pub fn with_capacity(cap: usize) -> Self {
-        let mut rv = Self::new();
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        handle_reserve(Self::try_with_capacity_in(cap, alloc))
+    }
+
+    pub fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut rv = Self::new_in(alloc);
 
-        rv.grow_to_cap(cap, None);
+        if cap > rv.cap {
+            rv.try_grow_to_cap(cap)?;
+        }
 
-        rv
+        Ok(rv)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> RawVec<T, A, G> {
+    const IS_ZST: bool = mem::size_of::<T>() == 0;
+
+    /// Swaps the growth policy, keeping the current buffer.
+    pub fn with_growth_policy<P: GrowthPolicy>(self, growth: P) -> RawVec<T, A, P> {
+        let mut me = mem::ManuallyDrop::new(self);
+        // the fields are read or dropped exactly once and `me` is never dropped
+        let alloc = unsafe {
+            std::ptr::drop_in_place(&mut me.growth);
+            std::ptr::read(&me.alloc)
+        };
+        RawVec {
+            ptr: me.ptr,
+            cap: me.cap,
+            alloc,
+            growth,
+        }
     }
 
     pub fn cap(&self) -> usize {
         self.cap
     }
 
-    fn grow_to_cap(&mut self, new_cap: usize, old_layout: Option<Layout>) {
-        let layout = Layout::array::<T>(new_cap).unwrap();
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
 
-        if let Some(old_layout) = old_layout {
-            unsafe {
-                let ptr = realloc(self.ptr.as_ptr() as *mut u8, old_layout, layout.size());
-                self.ptr = NonNull::new(ptr as *mut T).unwrap();
-            }
+    pub fn growth_policy(&self) -> &G {
+        &self.growth
+    }
+
+    fn current_layout(&self) -> Option<Layout> {
+        if Self::IS_ZST || self.cap == 0 {
+            None
         } else {
-            unsafe {
-                let ptr = alloc(layout);
-                self.ptr = NonNull::new(ptr as *mut T).unwrap();
-            }
+            // the layout was already validated when we allocated it
+            Layout::array::<T>(self.cap).ok()
         }
+    }
+
+    fn try_grow_to_cap(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        let layout = Layout::array::<T>(new_cap).map_err(|_| TryReserveError::CapacityOverflow)?;
+
+        let ptr = if let Some(old_layout) = self.current_layout() {
+            unsafe { self.alloc.grow(self.ptr.cast(), old_layout, layout) }
+        } else {
+            self.alloc.allocate(layout)
+        };
 
+        self.ptr = ptr
+            .map_err(|_| TryReserveError::AllocError { layout })?
+            .cast();
         self.cap = new_cap;
+
+        Ok(())
     }
 
-    pub fn grow(&mut self) {
-        let (new_cap, old_layout) = if self.cap == 0 {
-            (5, None)
+    fn required_cap(&self, len: usize, additional: usize) -> Result<Option<usize>, TryReserveError> {
+        let required = len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
+        // this also covers ZSTs, which are always at usize::MAX
+        if required <= self.cap {
+            Ok(None)
         } else {
-            let layout = Layout::array::<T>(self.cap).unwrap();
-            (self.cap * 2, Some(layout))
+            Ok(Some(required))
+        }
+    }
+
+    /// Makes room for at least `additional` more elements past `len`, growing per the policy.
+    pub fn try_reserve(&mut self, len: usize, additional: usize) -> Result<(), TryReserveError> {
+        if let Some(required) = self.required_cap(len, additional)? {
+            let new_cap = self
+                .growth
+                .next_capacity(self.cap, required, mem::size_of::<T>())
+                .max(required);
+            self.try_grow_to_cap(new_cap)?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes room for exactly `additional` more elements past `len`, ignoring the policy.
+    pub fn try_reserve_exact(&mut self, len: usize, additional: usize) -> Result<(), TryReserveError> {
+        if let Some(required) = self.required_cap(len, additional)? {
+            self.try_grow_to_cap(required)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn reserve(&mut self, len: usize, additional: usize) {
+        handle_reserve(self.try_reserve(len, additional))
+    }
+
+    pub fn reserve_exact(&mut self, len: usize, additional: usize) {
+        handle_reserve(self.try_reserve_exact(len, additional))
+    }
+
+    pub fn try_grow(&mut self) -> Result<(), TryReserveError> {
+        self.try_reserve(self.cap, 1)
+    }
+
+    pub fn grow(&mut self) {
+        handle_reserve(self.try_grow())
+    }
+
+    /// Shrinks the buffer down to `len` elements, freeing it entirely when `len` is 0.
+    pub fn shrink_to_fit(&mut self, len: usize) {
+        let Some(old_layout) = self.current_layout() else {
+            return;
         };
+        if len >= self.cap {
+            return;
+        }
+
+        if len == 0 {
+            unsafe {
+                self.alloc.deallocate(self.ptr.cast(), old_layout);
+            }
+            self.ptr = NonNull::dangling();
+            self.cap = 0;
+            return;
+        }
 
-        self.grow_to_cap(new_cap, old_layout);
+        // smaller than a layout we already allocated, so it can't overflow
+        let layout = Layout::array::<T>(len).unwrap();
+        match unsafe { self.alloc.shrink(self.ptr.cast(), old_layout, layout) } {
+            Ok(ptr) => {
+                self.ptr = ptr.cast();
+                self.cap = len;
+            }
+            Err(_) => alloc::handle_alloc_error(layout),
+        }
     }
 }
 
-impl<T> Drop for RawVec<T> {
+impl<T, A: Allocator, G: GrowthPolicy> Drop for RawVec<T, A, G> {
     fn drop(&mut self) {
-        if self.cap > 0 {
-            let layout = Layout::array::<T>(self.cap).unwrap();
+        if let Some(layout) = self.current_layout() {
             unsafe {
-                dealloc(self.ptr.as_ptr() as *mut u8, layout);
+                self.alloc.deallocate(self.ptr.cast(), layout);
             }
         }
     }
 }
 
+/// Turns a failed reservation into the same panic/abort std collections use.
+fn handle_reserve<R>(result: Result<R, TryReserveError>) -> R {
+    match result {
+        Ok(r) => r,
+        Err(TryReserveError::CapacityOverflow) => panic!("capacity overflow"),
+        Err(TryReserveError::AllocError { layout }) => alloc::handle_alloc_error(layout),
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
 
     #[test]
     fn rawvec_works() {
@@ -103,4 +461,161 @@ mod tests {
             assert_ne!(*ptr, 50);
         }
     }
+
+    /// Counts live allocations and refuses anything over `limit` bytes.
+    struct CountingAlloc {
+        live: Cell<usize>,
+        limit: usize,
+    }
+
+    impl CountingAlloc {
+        fn new(limit: usize) -> Self {
+            CountingAlloc { live: Cell::new(0), limit }
+        }
+    }
+
+    unsafe impl Allocator for CountingAlloc {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            if layout.size() > self.limit {
+                return Err(AllocError);
+            }
+            self.live.set(self.live.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn rawvec_custom_allocator_works() {
+        let a = CountingAlloc::new(1024);
+        let mut rv = RawVec::<u64, _>::new_in(&a);
+        assert_eq!(a.live.get(), 0);
+        rv.grow();
+        assert_eq!(a.live.get(), 1);
+        rv.grow();
+        rv.grow();
+        assert_eq!(rv.cap(), 20);
+        assert_eq!(a.live.get(), 1);
+        drop(rv);
+        assert_eq!(a.live.get(), 0);
+    }
+
+    #[test]
+    fn rawvec_try_grow_fails() {
+        let a = CountingAlloc::new(64);
+        let mut rv = RawVec::<u64, _>::try_with_capacity_in(8, &a).unwrap();
+        assert_eq!(
+            rv.try_grow(),
+            Err(TryReserveError::AllocError {
+                layout: Layout::array::<u64>(16).unwrap()
+            })
+        );
+        // a failed grow leaves the old buffer in place
+        assert_eq!(rv.cap(), 8);
+        assert_eq!(a.live.get(), 1);
+
+        assert_eq!(
+            RawVec::<u64>::try_with_capacity(usize::MAX).err(),
+            Some(TryReserveError::CapacityOverflow)
+        );
+    }
+
+    #[test]
+    fn rawvec_zst_never_allocates() {
+        let a = CountingAlloc::new(0);
+        let mut rv = RawVec::<(), _>::with_capacity_in(10, &a);
+        assert_eq!(rv.cap(), usize::MAX);
+        assert_eq!(rv.try_grow(), Err(TryReserveError::CapacityOverflow));
+        unsafe {
+            std::ptr::write(rv.ptr.as_ptr(), ());
+        }
+        drop(rv);
+        assert_eq!(a.live.get(), 0);
+    }
+
+    #[test]
+    fn growth_policies_work() {
+        assert_eq!(Doubling.next_capacity(0, 1, 8), 5);
+        assert_eq!(Doubling.next_capacity(10, 11, 8), 20);
+        assert_eq!(Doubling.next_capacity(10, 50, 8), 50);
+        assert_eq!(OneAndHalf.next_capacity(10, 11, 8), 15);
+        assert_eq!(FixedIncrement(3).next_capacity(0, 1, 8), 3);
+        assert_eq!(FixedIncrement(3).next_capacity(3, 4, 8), 6);
+        assert_eq!(PageAligned(4096).next_capacity(0, 1, 8), 512);
+        assert_eq!(PageAligned(4096).next_capacity(512, 513, 8), 1024);
+        assert_eq!(PageAligned(4096).next_capacity(0, 1, 3000), 5);
+
+        let mut rv = RawVec::<u32>::new().with_growth_policy(|cap: usize, _| cap + 2);
+        rv.grow();
+        assert_eq!(rv.cap(), 2);
+        rv.grow();
+        assert_eq!(rv.cap(), 4);
+        // the policy can't grow by less than what's required
+        rv.reserve(4, 10);
+        assert_eq!(rv.cap(), 14);
+    }
+
+    /// A policy that counts how many times it's been dropped.
+    struct DropCounting<'a>(&'a Cell<usize>);
+
+    impl GrowthPolicy for DropCounting<'_> {
+        fn next_capacity(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+            Doubling.next_capacity(cap, required, elem_size)
+        }
+    }
+
+    impl Drop for DropCounting<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn swapping_growth_policy_drops_the_old_one() {
+        let drops = Cell::new(0);
+        let mut rv = RawVec::<u32>::new().with_growth_policy(DropCounting(&drops));
+        rv.grow();
+        let rv = rv.with_growth_policy(DropCounting(&drops));
+        assert_eq!(drops.get(), 1);
+        assert_eq!(rv.cap(), 5);
+        let rv = rv.with_growth_policy(Doubling);
+        assert_eq!(drops.get(), 2);
+        drop(rv);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn reserve_and_shrink_work() {
+        let a = CountingAlloc::new(1024);
+        let mut rv = RawVec::<u64, _>::new_in(&a).with_growth_policy(FixedIncrement(4));
+        rv.reserve(0, 3);
+        assert_eq!(rv.cap(), 4);
+        rv.reserve(3, 1);
+        assert_eq!(rv.cap(), 4);
+        rv.reserve(4, 1);
+        assert_eq!(rv.cap(), 8);
+        rv.reserve_exact(8, 1);
+        assert_eq!(rv.cap(), 9);
+        unsafe {
+            std::ptr::write(rv.ptr.as_ptr().add(1), 42);
+        }
+
+        rv.shrink_to_fit(2);
+        assert_eq!(rv.cap(), 2);
+        unsafe {
+            assert_eq!(*rv.ptr.as_ptr().add(1), 42);
+        }
+        rv.shrink_to_fit(0);
+        assert_eq!(rv.cap(), 0);
+        assert_eq!(a.live.get(), 0);
+
+        assert_eq!(
+            rv.try_reserve(usize::MAX, 1),
+            Err(TryReserveError::CapacityOverflow)
+        );
+    }
 }
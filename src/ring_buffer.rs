@@ -1,42 +1,278 @@
 #![allow(dead_code)]
 
-use std::ops::{Index, IndexMut};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::{Index, IndexMut, Range};
 use std::ptr;
+use std::slice;
 
-use crate::rawvec::RawVec;
+use crate::array_list::ArrayList;
+use crate::rawvec::{Allocator, Doubling, Global, GrowthPolicy, RawVec, TryReserveError};
 
-pub struct RingBuffer<T> {
+pub mod mpmc;
+pub mod spsc;
+
+/// What a push does once the buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Drop the element at the other end to make room, handing it back as `Ok(Some(old))`.
+    #[default]
+    Overwrite,
+    /// Leave the buffer alone and hand the new element back as `Err(val)`.
+    Reject,
+    /// Reallocate per the growth policy, un-wrapping the contents, like `VecDeque`.
+    Grow,
+}
+
+pub struct RingBuffer<T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
+    // a physical index, or a free-running counter when `masked`
     head: usize,
-    buf: RawVec<T>,
+    buf: RawVec<T, A, G>,
     len: usize,
+    overflow: Overflow,
+    masked: bool,
 }
 
 impl<T> RingBuffer<T> {
     pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    pub fn try_with_capacity(cap: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+
+    /// An empty buffer that grows rather than overwrites, like `VecDeque`, with
+    /// masked indexing. `cap` is rounded up to a power of two for the mask.
+    pub fn growing(cap: usize) -> Self {
+        Self::with_capacity(cap)
+            .with_overflow(Overflow::Grow)
+            .with_masked_indexing()
+    }
+}
+
+impl<T, A: Allocator> RingBuffer<T, A> {
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
         RingBuffer {
             head: 0,
-            buf: RawVec::<T>::with_capacity(cap),
+            buf: RawVec::with_capacity_in(cap, alloc),
             len: 0,
+            overflow: Overflow::Overwrite,
+            masked: false,
+        }
+    }
+
+    pub fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Ok(RingBuffer {
+            head: 0,
+            buf: RawVec::try_with_capacity_in(cap, alloc)?,
+            len: 0,
+            overflow: Overflow::Overwrite,
+            masked: false,
+        })
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> RingBuffer<T, A, G> {
+    /// Swaps the growth policy used whenever the buffer needs more room.
+    pub fn with_growth_policy<P: GrowthPolicy>(self, growth: P) -> RingBuffer<T, A, P> {
+        let me = mem::ManuallyDrop::new(self);
+        // `me` is never dropped, so the buffer is moved out exactly once
+        let buf = unsafe { ptr::read(&me.buf) };
+        RingBuffer {
+            head: me.head,
+            buf: buf.with_growth_policy(growth),
+            len: me.len,
+            overflow: me.overflow,
+            masked: me.masked,
         }
     }
 
+    /// Picks what happens to pushes once the buffer is full. Buffers start out
+    /// with `Overflow::Overwrite`, except `RingBuffer::default()` which grows.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /**
+    Rounds the capacity up to a power of two and keeps it there, so indexing
+    can use a bit mask instead of `%`. The head then runs freely and is only
+    masked on access. Growing under `Overflow::Grow` doubles the capacity
+    regardless of the growth policy.
+    */
+    pub fn with_masked_indexing(mut self) -> Self {
+        let cap = self.buf.cap();
+        if mem::size_of::<T>() != 0 && cap != 0 && !cap.is_power_of_two() {
+            self.unwrap_contents();
+            self.buf.reserve_exact(self.len, cap.next_power_of_two() - self.len);
+        }
+        self.masked = true;
+        self
+    }
+
+    pub fn masked_indexing(&self) -> bool {
+        self.masked
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.cap()
+    }
+
     fn wrapped_index(&self, i: usize) -> usize {
-        (self.head + i) % self.buf.cap()
+        // every slot of a ZST buffer is the same (dangling) address,
+        // and an empty buffer has no slots at all
+        if mem::size_of::<T>() == 0 || self.buf.cap() == 0 {
+            return 0;
+        }
+
+        if self.masked {
+            self.head.wrapping_add(i) & (self.buf.cap() - 1)
+        } else {
+            (self.head + i) % self.buf.cap()
+        }
     }
 
-    pub fn push_back(&mut self, val: T) {
-        let i = self.wrapped_index(self.len);
+    /// `wrapped_index` for a buffer that's known to have slots, which skips the check for none.
+    fn slot(&self, i: usize) -> usize {
+        if mem::size_of::<T>() == 0 {
+            0
+        } else if self.masked {
+            self.head.wrapping_add(i) & (self.buf.cap() - 1)
+        } else {
+            (self.head + i) % self.buf.cap()
+        }
+    }
+
+    /// What `head` becomes after moving `n` slots forward.
+    fn head_after(&self, n: usize) -> usize {
+        if self.masked {
+            self.head.wrapping_add(n)
+        } else {
+            self.wrapped_index(n)
+        }
+    }
+
+    /// What `head` becomes after moving one slot back.
+    fn head_before(&self) -> usize {
+        if self.masked {
+            self.head.wrapping_sub(1)
+        } else {
+            self.wrapped_index(self.buf.cap().saturating_sub(1))
+        }
+    }
 
+    /**
+    Adds `val` after the last element. If the buffer is full, what happens
+    depends on its `Overflow` policy: the oldest element is overwritten and
+    returned as `Ok(Some(old))`, `val` is rejected as `Err(val)`, or the buffer
+    grows. Otherwise it returns `Ok(None)`.
+    */
+    pub fn push_back(&mut self, val: T) -> Result<Option<T>, T> {
+        if self.len == self.buf.cap() {
+            match self.overflow {
+                // nothing to overwrite, the new value is the oldest one
+                Overflow::Overwrite if self.buf.cap() == 0 => return Ok(Some(val)),
+                Overflow::Overwrite => {
+                    let i = self.wrapped_index(0);
+                    self.head = self.head_after(1);
+                    let old = unsafe { ptr::replace(self.buf.ptr.as_ptr().add(i), val) };
+                    return Ok(Some(old));
+                }
+                Overflow::Reject => return Err(val),
+                Overflow::Grow => self.grow(),
+            }
+        }
+
+        // there's room now, so there's at least one slot
+        let i = self.slot(self.len);
         unsafe {
-            let p = self.buf.ptr.as_ptr().add(i);
+            ptr::write(self.buf.ptr.as_ptr().add(i), val);
+        }
+        self.len += 1;
 
-            if self.len == self.buf.cap() {
-                ptr::replace(p, val);
-            } else {
-                self.len += 1;
-                ptr::write(p, val);
+        Ok(None)
+    }
+
+    /// Adds `val` before the first element, following the `Overflow` policy
+    /// like `push_back`, except that overwriting drops the newest element.
+    pub fn push_front(&mut self, val: T) -> Result<Option<T>, T> {
+        if self.len == self.buf.cap() {
+            match self.overflow {
+                Overflow::Overwrite if self.buf.cap() == 0 => return Ok(Some(val)),
+                Overflow::Overwrite => {
+                    // when full, the slot before the head is the last element
+                    self.head = self.head_before();
+                    let i = self.wrapped_index(0);
+                    let old = unsafe { ptr::replace(self.buf.ptr.as_ptr().add(i), val) };
+                    return Ok(Some(old));
+                }
+                Overflow::Reject => return Err(val),
+                Overflow::Grow => self.grow(),
             }
         }
+
+        self.head = self.head_before();
+        let i = self.wrapped_index(0);
+        unsafe {
+            ptr::write(self.buf.ptr.as_ptr().add(i), val);
+        }
+        self.len += 1;
+
+        Ok(None)
+    }
+
+    /// Grows a full buffer, un-wrapping it first so the new space ends up after the last element.
+    // kept out of line so the pushes that don't grow stay small enough to inline well
+    #[cold]
+    #[inline(never)]
+    fn grow(&mut self) {
+        self.unwrap_contents();
+        if self.masked {
+            let cap = self.buf.cap();
+            self.buf.reserve_exact(self.len, (cap + 1).next_power_of_two() - self.len);
+        } else {
+            self.buf.grow();
+        }
+    }
+
+    /// Moves the elements so they start at the front of the buffer without wrapping around.
+    fn unwrap_contents(&mut self) {
+        let cap = self.buf.cap();
+        let head = self.wrapped_index(0);
+
+        if head + self.len <= cap {
+            // a free-running head only means anything for the current capacity
+            self.head = head;
+            return;
+        }
+
+        let front_len = cap - head;
+        let back_len = self.len - front_len;
+
+        unsafe {
+            let p = self.buf.ptr.as_ptr();
+            // slide the front part down so it sits right after the wrapped around part:
+            // [back | front] is initialized and only needs rotating into [front | back]
+            ptr::copy(p.add(head), p.add(back_len), front_len);
+            slice::from_raw_parts_mut(p, self.len).rotate_left(back_len);
+        }
+
+        self.head = 0;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
@@ -44,20 +280,173 @@ impl<T> RingBuffer<T> {
             return None;
         }
 
-        let i = self.head;
-        self.head = self.wrapped_index(1);
+        let i = self.slot(0);
+        self.head = self.head_after(1);
         self.len -= 1;
 
         unsafe {
             Some(ptr::read(self.buf.ptr.as_ptr().add(i)))
         }
     }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        let i = self.wrapped_index(self.len);
+
+        unsafe { Some(ptr::read(self.buf.ptr.as_ptr().add(i))) }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let i = self.wrapped_index(index);
+        unsafe { Some(&*self.buf.ptr.as_ptr().add(i)) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let i = self.wrapped_index(index);
+        unsafe { Some(&mut *self.buf.ptr.as_ptr().add(i)) }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Rotates the buffer `n` places to the left, so the element at `n` becomes the front.
+    /// A full buffer only moves its head, otherwise this takes `min(n, len - n)` moves.
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotating by {n} is out of bounds for a length of {}", self.len);
+
+        if self.len == self.buf.cap() {
+            self.head = self.head_after(n);
+        } else if n <= self.len / 2 {
+            for _ in 0..n {
+                self.move_front_to_back();
+            }
+        } else {
+            for _ in 0..self.len - n {
+                self.move_back_to_front();
+            }
+        }
+    }
+
+    /// Rotates the buffer `n` places to the right, so the front element ends up at `n`.
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotating by {n} is out of bounds for a length of {}", self.len);
+
+        self.rotate_left(self.len - n);
+    }
+
+    /// Only called when the buffer isn't full, so there's always a free slot after the back.
+    fn move_front_to_back(&mut self) {
+        unsafe {
+            let p = self.buf.ptr.as_ptr();
+            let src = self.wrapped_index(0);
+            let dst = self.wrapped_index(self.len);
+            ptr::copy_nonoverlapping(p.add(src), p.add(dst), 1);
+        }
+        self.head = self.head_after(1);
+    }
+
+    /// Only called when the buffer isn't full, so there's always a free slot before the head.
+    fn move_back_to_front(&mut self) {
+        let src = self.wrapped_index(self.len - 1);
+        self.head = self.head_before();
+        unsafe {
+            let p = self.buf.ptr.as_ptr();
+            let dst = self.wrapped_index(0);
+            ptr::copy_nonoverlapping(p.add(src), p.add(dst), 1);
+        }
+    }
+
+    /// The contents in order, as the part up to the end of the buffer and the
+    /// part that wrapped around to its start (empty unless it wraps).
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.slice_ranges();
+        let ptr = self.buf.ptr.as_ptr();
+
+        unsafe {
+            (
+                slice::from_raw_parts(ptr.add(front.start), front.len()),
+                slice::from_raw_parts(ptr.add(back.start), back.len()),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.slice_ranges();
+        let ptr = self.buf.ptr.as_ptr();
+
+        // the two ranges never overlap
+        unsafe {
+            (
+                slice::from_raw_parts_mut(ptr.add(front.start), front.len()),
+                slice::from_raw_parts_mut(ptr.add(back.start), back.len()),
+            )
+        }
+    }
+
+    /// Rearranges the contents so they don't wrap around, returning them as one slice.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.unwrap_contents();
+        self.as_mut_slices().0
+    }
+
+    /// The physical index ranges of the elements, in order. The second one
+    /// is empty unless the contents wrap around the end of the buffer.
+    fn slice_ranges(&self) -> (Range<usize>, Range<usize>) {
+        if self.len == 0 {
+            return (0..0, 0..0);
+        }
+
+        let head = self.wrapped_index(0);
+        let front_len = self.len.min(self.buf.cap() - head);
+        (head..head + front_len, 0..self.len - front_len)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
 }
 
-impl<T> Index<usize> for RingBuffer<T> {
+impl<T, A: Allocator, G: GrowthPolicy> Index<usize> for RingBuffer<T, A, G> {
     type Output = T;
 
-    fn index<'a>(&'a self, index: usize) -> &'a T {
+    fn index(&self, index: usize) -> &T {
         if index >= self.len {
             panic!("Index out of bounds");
         }
@@ -71,8 +460,8 @@ impl<T> Index<usize> for RingBuffer<T> {
     }
 }
 
-impl<T> IndexMut<usize> for RingBuffer<T> {
-    fn index_mut<'a>(&'a mut self, index: usize) -> &'a mut Self::Output {
+impl<T, A: Allocator, G: GrowthPolicy> IndexMut<usize> for RingBuffer<T, A, G> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         if index >= self.len {
             panic!("Index out of bounds");
         }
@@ -86,25 +475,204 @@ impl<T> IndexMut<usize> for RingBuffer<T> {
     }
 }
 
-impl<T> Drop for RingBuffer<T> {
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> Clone for RingBuffer<T, A, G> {
+    fn clone(&self) -> Self {
+        let buf = RawVec::with_capacity_in(self.buf.cap(), self.buf.allocator().clone())
+            .with_growth_policy(self.buf.growth_policy().clone());
+        let mut rb = RingBuffer {
+            head: 0,
+            buf,
+            len: 0,
+            overflow: self.overflow,
+            masked: self.masked,
+        };
+        rb.extend(self.iter().cloned());
+        rb
+    }
+}
+
+/// An empty buffer that grows as needed, like `VecDeque`.
+impl<T> Default for RingBuffer<T> {
+    fn default() -> Self {
+        Self::with_capacity(0).with_overflow(Overflow::Grow)
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator, G: GrowthPolicy> fmt::Debug for RingBuffer<T, A, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, A: Allocator, G: GrowthPolicy> PartialEq for RingBuffer<T, A, G> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: Allocator, G: GrowthPolicy> Eq for RingBuffer<T, A, G> {}
+
+impl<T: PartialOrd, A: Allocator, G: GrowthPolicy> PartialOrd for RingBuffer<T, A, G> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, A: Allocator, G: GrowthPolicy> Ord for RingBuffer<T, A, G> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, A: Allocator, G: GrowthPolicy> Hash for RingBuffer<T, A, G> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        self.iter().for_each(|v| v.hash(state));
+    }
+}
+
+/// Pushes every item to the back, following the `Overflow` policy: once full,
+/// an overwriting buffer keeps only the newest items and a rejecting one the oldest.
+impl<T, A: Allocator, G: GrowthPolicy> Extend<T> for RingBuffer<T, A, G> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            let _ = self.push_back(v);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, A: Allocator, G: GrowthPolicy> Extend<&'a T> for RingBuffer<T, A, G> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+/// Collects into a buffer exactly big enough for every item, which grows like
+/// `RingBuffer::default()` when pushed to after that, as `VecDeque` would.
+impl<T> FromIterator<T> for RingBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items = ArrayList::from_iter(iter);
+        let mut rb = RingBuffer::with_capacity(items.len()).with_overflow(Overflow::Grow);
+        rb.extend(items);
+        rb
+    }
+}
+
+impl<T> From<Vec<T>> for RingBuffer<T> {
+    fn from(v: Vec<T>) -> Self {
+        let mut rb = RingBuffer::with_capacity(v.len()).with_overflow(Overflow::Grow);
+        rb.extend(v);
+        rb
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for RingBuffer<T> {
+    fn from(arr: [T; N]) -> Self {
+        let mut rb = RingBuffer::with_capacity(N).with_overflow(Overflow::Grow);
+        rb.extend(arr);
+        rb
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for RingBuffer<T, A, G> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a RingBuffer<T, A, G> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a mut RingBuffer<T, A, G> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Walks the front half of the buffer, then the wrapped around back half.
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len();
+        (n, Some(n))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len();
+        (n, Some(n))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::rng;
 
     #[test]
     fn ring_buffer_works() {
         let mut rb = RingBuffer::<u8>::with_capacity(8);
         assert_eq!(rb.len, 0);
         assert_eq!(rb.pop_front(), None);
-        rb.push_back(1);
+        rb.push_back(1).unwrap();
         assert_eq!(rb[0], 1);
-        rb.push_back(9);
-        rb.push_back(7);
+        rb.push_back(9).unwrap();
+        rb.push_back(7).unwrap();
         rb[2] = 14;
         assert_eq!(rb.len, 3);
         assert_eq!(rb.pop_front(), Some(1));
@@ -117,12 +685,302 @@ mod tests {
     #[test]
     fn string_ring_works() {
         let mut rb = RingBuffer::<String>::with_capacity(2);
-        rb.push_back("Hey".to_string());
+        rb.push_back("Hey".to_string()).unwrap();
         rb[0] = "META".to_string();
-        rb.push_back("You".to_string());
+        rb.push_back("You".to_string()).unwrap();
+        assert_eq!(rb.len, 2);
+        // the oldest element is the one that gets overwritten
+        assert_eq!(rb.push_back("Guys".to_string()), Ok(Some("META".to_string())));
         assert_eq!(rb.len, 2);
-        rb.push_back("Guys".to_string());
+        assert_eq!(rb.pop_front(), Some("You".to_string()));
+        assert_eq!(rb.pop_front(), Some("Guys".to_string()));
+        assert_eq!(rb.pop_front(), None);
+    }
+
+    #[test]
+    fn ring_iter_works() {
+        let mut rb = RingBuffer::<u8>::with_capacity(4);
+        assert_eq!(rb.iter().next(), None);
+        for i in 0..4 {
+            rb.push_back(i).unwrap();
+        }
+        rb.pop_front();
+        rb.pop_front();
+        rb.push_back(4).unwrap();
+        rb.push_back(5).unwrap();
+        // the contents now wrap around the end of the buffer
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert_eq!(rb.iter().rev().copied().collect::<Vec<_>>(), vec![5, 4, 3, 2]);
+        assert_eq!(rb.iter().len(), 4);
+
+        let mut it = rb.iter();
+        assert_eq!(it.next(), Some(&2));
+        assert_eq!(it.next_back(), Some(&5));
+        assert_eq!(it.len(), 2);
+
+        for v in &mut rb {
+            *v *= 10;
+        }
+        assert_eq!((&rb).into_iter().copied().collect::<Vec<_>>(), vec![20, 30, 40, 50]);
+        assert_eq!(rb.iter_mut().rev().map(|v| *v).collect::<Vec<_>>(), vec![50, 40, 30, 20]);
+        assert_eq!(rb.pop_front(), Some(20));
+    }
+
+    #[test]
+    fn zst_ring_works() {
+        #[derive(Debug, PartialEq)]
+        struct Marker;
+
+        let mut rb = RingBuffer::<Marker>::with_capacity(0);
+        assert_eq!(rb.buf.cap(), usize::MAX);
+        for _ in 0..100 {
+            rb.push_back(Marker).unwrap();
+        }
+        assert_eq!(rb.len, 100);
+        assert_eq!(rb[99], Marker);
+        assert_eq!(rb.iter().len(), 100);
+        assert_eq!(rb.iter().rev().count(), 100);
+        for _ in 0..100 {
+            assert_eq!(rb.pop_front(), Some(Marker));
+        }
+        assert_eq!(rb.pop_front(), None);
+    }
+
+    #[test]
+    fn zero_capacity_ring_works() {
+        let mut rb = RingBuffer::<String>::with_capacity(0);
+        assert_eq!(rb.push_back("Hey".to_string()), Ok(Some("Hey".to_string())));
+        assert_eq!(rb.len, 0);
+        assert_eq!(rb.pop_front(), None);
+    }
+
+    #[test]
+    fn overwrite_ring_keeps_newest() {
+        let mut rb = RingBuffer::<u8>::with_capacity(3);
+        for i in 0..3 {
+            assert_eq!(rb.push_back(i), Ok(None));
+        }
+        assert_eq!(rb.push_back(3), Ok(Some(0)));
+        assert_eq!(rb.push_back(4), Ok(Some(1)));
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(rb.pop_front(), Some(2));
+        assert_eq!(rb.push_back(5), Ok(None));
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn reject_ring_keeps_oldest() {
+        let mut rb = RingBuffer::<String>::with_capacity(2).with_overflow(Overflow::Reject);
+        rb.push_back("Hey".to_string()).unwrap();
+        rb.push_back("You".to_string()).unwrap();
+        assert_eq!(rb.push_back("Guys".to_string()), Err("Guys".to_string()));
         assert_eq!(rb.len, 2);
+        assert_eq!(rb.pop_front(), Some("Hey".to_string()));
+        assert_eq!(rb.push_back("Guys".to_string()), Ok(None));
+        assert_eq!(rb.pop_front(), Some("You".to_string()));
         assert_eq!(rb.pop_front(), Some("Guys".to_string()));
+
+        let mut rb = RingBuffer::<u8>::with_capacity(0).with_overflow(Overflow::Reject);
+        assert_eq!(rb.push_back(1), Err(1));
+    }
+
+    #[test]
+    fn growing_ring_unwraps() {
+        let mut rb = RingBuffer::<String>::with_capacity(4).with_overflow(Overflow::Grow);
+        for i in 0..4 {
+            rb.push_back(i.to_string()).unwrap();
+        }
+        rb.pop_front();
+        rb.pop_front();
+        rb.push_back("4".to_string()).unwrap();
+        rb.push_back("5".to_string()).unwrap();
+        assert_eq!(rb.head, 2);
+
+        // full and wrapped, so this has to un-wrap before growing
+        assert_eq!(rb.push_back("6".to_string()), Ok(None));
+        assert_eq!(rb.head, 0);
+        assert_eq!(rb.capacity(), 8);
+        assert_eq!(rb.iter().cloned().collect::<Vec<_>>(), vec!["2", "3", "4", "5", "6"]);
+
+        let mut rb = RingBuffer::<u32>::default();
+        rb.extend(0..100);
+        assert_eq!(rb.len(), 100);
+        assert!(rb.iter().copied().eq(0..100));
+
+        let rb = RingBuffer::<u32>::growing(5);
+        assert_eq!((rb.capacity(), rb.overflow()), (8, Overflow::Grow));
+        assert!(rb.masked_indexing());
+
+        // conversions come out full, but grow rather than drop the front
+        let mut rb = RingBuffer::from(vec![1, 2, 3]);
+        assert_eq!(rb.push_back(4), Ok(None));
+        assert!(rb.iter().copied().eq(1..=4));
+    }
+
+    #[test]
+    fn growing_ring_follows_growth_policy() {
+        use crate::rawvec::FixedIncrement;
+
+        let mut rb = RingBuffer::<u32>::with_capacity(2)
+            .with_overflow(Overflow::Grow)
+            .with_growth_policy(FixedIncrement(3));
+        rb.extend([1, 2, 3]);
+        assert_eq!(rb.capacity(), 5);
+        assert_eq!(rb.overflow(), Overflow::Grow);
+        assert!(rb.iter().copied().eq(1..=3));
+    }
+
+    #[test]
+    fn double_ended_ring_works() {
+        let mut rb = RingBuffer::<u8>::with_capacity(4).with_overflow(Overflow::Reject);
+        assert_eq!(rb.front(), None);
+        assert_eq!(rb.back(), None);
+        assert_eq!(rb.pop_back(), None);
+        rb.push_back(2).unwrap();
+        rb.push_front(1).unwrap();
+        rb.push_back(3).unwrap();
+        rb.push_front(0).unwrap();
+        assert_eq!(rb.push_front(9), Err(9));
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(rb.front(), Some(&0));
+        assert_eq!(rb.back(), Some(&3));
+        *rb.front_mut().unwrap() = 10;
+        *rb.back_mut().unwrap() = 30;
+        assert_eq!(rb.pop_back(), Some(30));
+        assert_eq!(rb.pop_front(), Some(10));
+        assert_eq!(rb.pop_back(), Some(2));
+        assert_eq!(rb.pop_back(), Some(1));
+        assert_eq!(rb.pop_back(), None);
+
+        let mut rb = RingBuffer::<u8>::with_capacity(3);
+        rb.extend([1, 2, 3]);
+        // overwriting from the front drops the back
+        assert_eq!(rb.push_front(0), Ok(Some(3)));
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        let mut rb = RingBuffer::<u8>::default();
+        for i in 0..10 {
+            rb.push_front(i).unwrap();
+        }
+        assert!(rb.iter().copied().eq((0..10).rev()));
+    }
+
+    #[test]
+    fn rotate_ring_works() {
+        let mut full = RingBuffer::<u8>::with_capacity(5);
+        full.extend(0..5);
+        let mut partial = RingBuffer::<u8>::with_capacity(8);
+        partial.extend(0..5);
+
+        for rb in [&mut full, &mut partial] {
+            rb.rotate_left(2);
+            assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4, 0, 1]);
+            rb.rotate_left(4);
+            assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 0]);
+            rb.rotate_right(3);
+            assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![3, 4, 0, 1, 2]);
+            rb.rotate_right(0);
+            rb.rotate_left(5);
+            assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![3, 4, 0, 1, 2]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rotate_out_of_bounds_panics() {
+        let mut rb = RingBuffer::<u8>::with_capacity(5);
+        rb.extend(0..2);
+        rb.rotate_left(3);
+    }
+
+    #[test]
+    fn ring_slices_work() {
+        let mut rb = RingBuffer::<u8>::with_capacity(5);
+        assert_eq!(rb.as_slices(), (&[][..], &[][..]));
+        rb.extend(0..4);
+        assert_eq!(rb.as_slices(), (&[0, 1, 2, 3][..], &[][..]));
+        rb.pop_front();
+        rb.pop_front();
+        rb.pop_front();
+        rb.extend(4..7);
+        assert_eq!(rb.as_slices(), (&[3, 4][..], &[5, 6][..]));
+        rb.as_mut_slices().1[0] = 50;
+
+        assert_eq!(rb.make_contiguous(), &[3, 4, 50, 6]);
+        assert_eq!(rb.as_slices(), (&[3, 4, 50, 6][..], &[][..]));
+        rb.make_contiguous().sort_by(|a, b| b.cmp(a));
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![50, 6, 4, 3]);
+        rb.push_back(7).unwrap();
+        assert_eq!(rb.pop_front(), Some(50));
+
+        let mut rb = RingBuffer::<String>::with_capacity(4);
+        rb.extend(["a", "b", "c", "d", "e", "f"].map(String::from));
+        assert_eq!(rb.make_contiguous().join(""), "cdef");
+    }
+
+    #[test]
+    fn masked_indexing_rounds_up() {
+        let mut rb = RingBuffer::<u8>::with_capacity(5);
+        rb.extend(0..5);
+        rb.pop_front();
+        rb.push_back(5).unwrap();
+        let mut rb = rb.with_masked_indexing();
+        assert!(rb.masked_indexing());
+        assert_eq!(rb.capacity(), 8);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        rb.extend(6..12);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![4, 5, 6, 7, 8, 9, 10, 11]);
+
+        let mut rb = RingBuffer::<u8>::default().with_masked_indexing();
+        rb.extend(0..9);
+        assert_eq!(rb.capacity(), 16);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), (0..9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn masked_head_runs_past_usize_max() {
+        let mut rb = RingBuffer::<u32>::with_capacity(4).with_masked_indexing();
+        rb.head = usize::MAX - 1;
+        rb.extend(0..4);
+        assert_eq!(rb.as_slices(), (&[0, 1][..], &[2, 3][..]));
+        for i in 4..10 {
+            assert_eq!(rb.push_back(i), Ok(Some(i - 4)));
+        }
+        assert_eq!(rb.push_front(0), Ok(Some(9)));
+        rb.rotate_left(1);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![6, 7, 8, 0]);
+        assert_eq!(rb.pop_back(), Some(0));
+        assert_eq!(rb.pop_front(), Some(6));
+        rb.rotate_right(1);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), vec![8, 7]);
+    }
+
+    #[test]
+    fn masked_matches_modulo() {
+        // seeded, so the same operations run against both modes
+        let mut next = rng(0x2545_f491);
+
+        for overflow in [Overflow::Overwrite, Overflow::Reject, Overflow::Grow] {
+            let mut modulo = RingBuffer::<u64>::with_capacity(8).with_overflow(overflow);
+            let mut masked = RingBuffer::<u64>::with_capacity(8)
+                .with_overflow(overflow)
+                .with_masked_indexing();
+
+            for _ in 0..2000 {
+                let v = next() as u64;
+                match v % 6 {
+                    0 | 1 => assert_eq!(modulo.push_back(v), masked.push_back(v)),
+                    2 => assert_eq!(modulo.push_front(v), masked.push_front(v)),
+                    3 => assert_eq!(modulo.pop_front(), masked.pop_front()),
+                    4 => assert_eq!(modulo.pop_back(), masked.pop_back()),
+                    _ => {
+                        let n = v as usize % (modulo.len() + 1);
+                        modulo.rotate_left(n);
+                        masked.rotate_left(n);
+                    }
+                }
+                assert_eq!(modulo, masked);
+            }
+        }
     }
 }
@@ -0,0 +1,718 @@
+/*!
+A bounded multi-producer multi-consumer channel.
+
+The queue itself is Dmitry Vyukov's: every slot carries a sequence number that
+says whose turn it is. A slot at position `pos` is free for the sender that
+claims `pos` when its sequence is `pos`, and holds a value for the receiver
+that claims `pos` when its sequence is `pos + 1`. Positions keep the lap in
+their high bits, above a power of two larger than the capacity, so a full slot
+from the last lap never looks free, even with a single slot.
+
+Claiming a position is one CAS on `tail` or `head`, so senders and receivers
+only contend with their own kind. Blocking is layered on top with a mutex and
+two condvars, which are only touched when someone is actually waiting.
+*/
+
+use std::cell::UnsafeCell;
+use std::error::Error;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::spsc::CachePadded;
+use crate::rawvec::RawVec;
+
+/// An error returned from `Sender::send` when every receiver is gone. Holds the unsent value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+/// An error returned from `Sender::try_send`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+/// An error returned from `Sender::send_timeout`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SendTimeoutError<T> {
+    Timeout(T),
+    Disconnected(T),
+}
+
+/// An error returned from `Receiver::recv` when the channel is empty and every sender is gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+/// An error returned from `Receiver::try_recv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+/// An error returned from `Receiver::recv_timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+// like std, the debug output leaves out the value so `T` doesn't need to be `Debug`
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "Full(..)"),
+            TrySendError::Disconnected(_) => write!(f, "Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => write!(f, "Timeout(..)"),
+            SendTimeoutError::Disconnected(_) => write!(f, "Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending on a closed channel")
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "sending on a full channel"),
+            TrySendError::Disconnected(_) => write!(f, "sending on a closed channel"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => write!(f, "timed out waiting on send operation"),
+            SendTimeoutError::Disconnected(_) => write!(f, "sending on a closed channel"),
+        }
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiving on a closed channel")
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving on an empty channel"),
+            TryRecvError::Disconnected => write!(f, "receiving on a closed channel"),
+        }
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on receive operation"),
+            RecvTimeoutError::Disconnected => write!(f, "receiving on a closed channel"),
+        }
+    }
+}
+
+impl<T> Error for SendError<T> {}
+impl<T> Error for TrySendError<T> {}
+impl<T> Error for SendTimeoutError<T> {}
+impl Error for RecvError {}
+impl Error for TryRecvError {}
+impl Error for RecvTimeoutError {}
+
+/// How often a blocked `send`/`recv` yields to the other side before paying for the condvar.
+const YIELDS_BEFORE_PARKING: u32 = 8;
+
+struct Slot<T> {
+    seq: AtomicUsize,
+    val: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Who's parked on a condvar, so the fast path can skip the mutex when nobody is.
+struct Waiters {
+    count: AtomicUsize,
+    cond: Condvar,
+}
+
+impl Waiters {
+    fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            cond: Condvar::new(),
+        }
+    }
+}
+
+struct Shared<T> {
+    buf: RawVec<Slot<T>>,
+    cap: usize,
+    // a power of two above `cap`; positions are a lap count plus a slot index
+    one_lap: usize,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    senders: AtomicUsize,
+    receivers: AtomicUsize,
+    lock: Mutex<()>,
+    // senders wait here for a free slot, receivers for a value
+    not_full: Waiters,
+    not_empty: Waiters,
+}
+
+// a value is only ever touched by the one thread that claimed its slot
+unsafe impl<T: Send> Send for Shared<T> {}
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    fn slot(&self, index: usize) -> &Slot<T> {
+        unsafe { &*self.buf.ptr.as_ptr().add(index) }
+    }
+
+    /// The position after `pos`, moving to the next lap after the last slot.
+    fn next(&self, pos: usize) -> usize {
+        let index = pos & (self.one_lap - 1);
+        if index + 1 < self.cap {
+            pos + 1
+        } else {
+            (pos & !(self.one_lap - 1)).wrapping_add(self.one_lap)
+        }
+    }
+
+    fn push(&self, val: T) -> Result<(), T> {
+        let mut pos = self.tail.0.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos & (self.one_lap - 1));
+            let seq = slot.seq.load(Ordering::Acquire);
+
+            if seq == pos {
+                match self.tail.0.compare_exchange_weak(
+                    pos,
+                    self.next(pos),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.val.get()).write(val) };
+                        slot.seq.store(pos.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if seq.wrapping_add(self.one_lap) == pos.wrapping_add(1) {
+                // the slot still holds last lap's value, so the receivers are a whole lap behind
+                return Err(val);
+            } else {
+                pos = self.tail.0.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<T> {
+        let mut pos = self.head.0.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos & (self.one_lap - 1));
+            let seq = slot.seq.load(Ordering::Acquire);
+
+            if seq == pos.wrapping_add(1) {
+                match self.head.0.compare_exchange_weak(
+                    pos,
+                    self.next(pos),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let val = unsafe { (*slot.val.get()).assume_init_read() };
+                        slot.seq.store(pos.wrapping_add(self.one_lap), Ordering::Release);
+                        return Some(val);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if seq == pos {
+                // nothing's been written here this lap, or the sender is still writing
+                return None;
+            } else {
+                pos = self.head.0.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.0.load(Ordering::SeqCst);
+            let head = self.head.0.load(Ordering::SeqCst);
+            // only trust the pair if tail didn't move while head was read
+            if self.tail.0.load(Ordering::SeqCst) == tail {
+                return self.distance(head, tail);
+            }
+        }
+    }
+
+    fn distance(&self, head: usize, tail: usize) -> usize {
+        let hix = head & (self.one_lap - 1);
+        let tix = tail & (self.one_lap - 1);
+        if hix < tix {
+            tix - hix
+        } else if hix > tix {
+            self.cap - hix + tix
+        } else if head == tail {
+            0
+        } else {
+            self.cap
+        }
+    }
+
+    fn wake(&self, waiters: &Waiters) {
+        // pairs with the fence in `wait`: either it sees our change, or we see it waiting
+        atomic::fence(Ordering::SeqCst);
+        if waiters.count.load(Ordering::Relaxed) > 0 {
+            let _guard = self.lock.lock().unwrap();
+            waiters.cond.notify_one();
+        }
+    }
+
+    fn wake_all(&self) {
+        let _guard = self.lock.lock().unwrap();
+        self.not_full.cond.notify_all();
+        self.not_empty.cond.notify_all();
+    }
+
+    /// Parks until `ready` holds or `deadline` passes. Returns `false` on timeout.
+    fn wait(&self, waiters: &Waiters, deadline: Option<Instant>, ready: impl Fn() -> bool) -> bool {
+        let mut guard = self.lock.lock().unwrap();
+        waiters.count.fetch_add(1, Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+
+        let mut in_time = true;
+        while !ready() {
+            match deadline {
+                None => guard = waiters.cond.wait(guard).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        in_time = false;
+                        break;
+                    }
+                    guard = waiters.cond.wait_timeout(guard, deadline - now).unwrap().0;
+                }
+            }
+        }
+
+        waiters.count.fetch_sub(1, Ordering::Relaxed);
+        in_time
+    }
+
+    fn send(&self, mut val: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let mut yields = 0;
+        loop {
+            if self.receivers.load(Ordering::SeqCst) == 0 {
+                return Err(SendTimeoutError::Disconnected(val));
+            }
+            match self.push(val) {
+                Ok(()) => {
+                    self.wake(&self.not_empty);
+                    return Ok(());
+                }
+                Err(back) => val = back,
+            }
+
+            if yields < YIELDS_BEFORE_PARKING {
+                yields += 1;
+                thread::yield_now();
+                continue;
+            }
+            let ready = || self.len() < self.cap || self.receivers.load(Ordering::SeqCst) == 0;
+            if !self.wait(&self.not_full, deadline, ready) {
+                return Err(SendTimeoutError::Timeout(val));
+            }
+        }
+    }
+
+    fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let mut yields = 0;
+        loop {
+            if let Some(val) = self.pop() {
+                self.wake(&self.not_full);
+                return Ok(val);
+            }
+            if self.senders.load(Ordering::SeqCst) == 0 {
+                // a send may have landed just before the last sender left
+                return match self.pop() {
+                    Some(val) => Ok(val),
+                    None => Err(RecvTimeoutError::Disconnected),
+                };
+            }
+
+            if yields < YIELDS_BEFORE_PARKING {
+                yields += 1;
+                thread::yield_now();
+                continue;
+            }
+            let ready = || self.len() > 0 || self.senders.load(Ordering::SeqCst) == 0;
+            if !self.wait(&self.not_empty, deadline, ready) {
+                return Err(RecvTimeoutError::Timeout);
+            }
+        }
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let head = *self.head.0.get_mut();
+        let tail = *self.tail.0.get_mut();
+
+        // nobody's left mid-push, so everything between head and tail is a value
+        let hix = head & (self.one_lap - 1);
+        for i in 0..self.distance(head, tail) {
+            let index = if hix + i < self.cap { hix + i } else { hix + i - self.cap };
+            unsafe { ptr::drop_in_place((*self.slot(index).val.get()).as_mut_ptr()) };
+        }
+    }
+}
+
+/// The sending half of a channel made by `bounded`. Clone it for more producers.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// The receiving half of a channel made by `bounded`. Clone it for more consumers.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/**
+Makes a channel holding up to `cap` values in flight.
+
+# Panics
+Panics if `cap` is 0; there's no rendezvous mode.
+*/
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(cap > 0, "mpmc channel capacity must be non-zero");
+
+    let buf = RawVec::<Slot<T>>::with_capacity(cap);
+    for i in 0..cap {
+        unsafe {
+            ptr::write(
+                buf.ptr.as_ptr().add(i),
+                Slot {
+                    seq: AtomicUsize::new(i),
+                    val: UnsafeCell::new(MaybeUninit::uninit()),
+                },
+            )
+        };
+    }
+
+    let shared = Arc::new(Shared {
+        buf,
+        cap,
+        one_lap: (cap + 1).next_power_of_two(),
+        head: CachePadded(AtomicUsize::new(0)),
+        tail: CachePadded(AtomicUsize::new(0)),
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1),
+        lock: Mutex::new(()),
+        not_full: Waiters::new(),
+        not_empty: Waiters::new(),
+    });
+
+    (Sender { shared: shared.clone() }, Receiver { shared })
+}
+
+impl<T> Sender<T> {
+    /// Sends `val`, blocking while the channel is full. Fails once every receiver is gone.
+    pub fn send(&self, val: T) -> Result<(), SendError<T>> {
+        self.shared.send(val, None).map_err(|e| match e {
+            SendTimeoutError::Disconnected(val) | SendTimeoutError::Timeout(val) => SendError(val),
+        })
+    }
+
+    pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+        if self.shared.receivers.load(Ordering::SeqCst) == 0 {
+            return Err(TrySendError::Disconnected(val));
+        }
+        match self.shared.push(val) {
+            Ok(()) => {
+                self.shared.wake(&self.shared.not_empty);
+                Ok(())
+            }
+            Err(val) => Err(TrySendError::Full(val)),
+        }
+    }
+
+    pub fn send_timeout(&self, val: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        // a timeout too long to represent, like `Duration::MAX`, means no deadline
+        self.shared.send(val, Instant::now().checked_add(timeout))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.cap
+    }
+
+    /// A snapshot; other threads may be sending and receiving at the same time.
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.shared.cap
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receives a value, blocking while the channel is empty. Fails once it's empty and every sender is gone.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.shared.recv(None).map_err(|_| RecvError)
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        if let Some(val) = self.shared.pop() {
+            self.shared.wake(&self.shared.not_full);
+            return Ok(val);
+        }
+        if self.shared.senders.load(Ordering::SeqCst) == 0 {
+            return self.shared.pop().ok_or(TryRecvError::Disconnected);
+        }
+        Err(TryRecvError::Empty)
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.shared.recv(Instant::now().checked_add(timeout))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.cap
+    }
+
+    /// A snapshot; other threads may be sending and receiving at the same time.
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.shared.cap
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.wake_all();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if self.shared.receivers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.wake_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mpmc_works() {
+        let (tx, rx) = bounded::<String>(2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        tx.send("Hey".to_string()).unwrap();
+        tx.try_send("You".to_string()).unwrap();
+        assert!(tx.is_full());
+        assert_eq!(
+            tx.try_send("Guys".to_string()),
+            Err(TrySendError::Full("Guys".to_string()))
+        );
+        assert_eq!(rx.recv(), Ok("Hey".to_string()));
+        tx.send("Guys".to_string()).unwrap();
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.try_recv(), Ok("You".to_string()));
+        assert_eq!(rx.recv(), Ok("Guys".to_string()));
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn timeouts_expire() {
+        let (tx, rx) = bounded(1);
+        let wait = Duration::from_millis(20);
+        assert_eq!(rx.recv_timeout(wait), Err(RecvTimeoutError::Timeout));
+        tx.send(1).unwrap();
+        assert_eq!(tx.send_timeout(2, wait), Err(SendTimeoutError::Timeout(2)));
+        assert_eq!(rx.recv_timeout(wait), Ok(1));
+        assert_eq!(tx.send_timeout(2, wait), Ok(()));
+    }
+
+    #[test]
+    fn endless_timeouts_just_wait() {
+        let (tx, rx) = bounded(1);
+        assert_eq!(tx.send_timeout(1, Duration::MAX), Ok(()));
+        assert_eq!(rx.recv_timeout(Duration::MAX), Ok(1));
+
+        let waiter = thread::spawn(move || rx.recv_timeout(Duration::MAX));
+        thread::sleep(Duration::from_millis(20));
+        tx.send(2).unwrap();
+        assert_eq!(waiter.join().unwrap(), Ok(2));
+    }
+
+    #[test]
+    fn disconnects_when_last_half_drops() {
+        let (tx, rx) = bounded(4);
+        let tx2 = tx.clone();
+        tx.send(1).unwrap();
+        drop(tx);
+        tx2.send(2).unwrap();
+        drop(tx2);
+        // whatever was sent still comes out before the disconnect does
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+
+        let (tx, rx) = bounded(4);
+        let rx2 = rx.clone();
+        drop(rx);
+        tx.send(1).unwrap();
+        drop(rx2);
+        assert_eq!(tx.send(2), Err(SendError(2)));
+        assert_eq!(tx.try_send(3), Err(TrySendError::Disconnected(3)));
+    }
+
+    #[test]
+    fn blocked_halves_wake_on_disconnect() {
+        let (tx, rx) = bounded::<u32>(1);
+        let waiter = thread::spawn(move || rx.recv());
+        thread::sleep(Duration::from_millis(20));
+        drop(tx);
+        assert_eq!(waiter.join().unwrap(), Err(RecvError));
+
+        let (tx, rx) = bounded(1);
+        tx.send(1).unwrap();
+        let waiter = thread::spawn(move || tx.send(2));
+        thread::sleep(Duration::from_millis(20));
+        drop(rx);
+        assert_eq!(waiter.join().unwrap(), Err(SendError(2)));
+    }
+
+    #[test]
+    fn leftovers_are_dropped() {
+        let marker = Arc::new(());
+        let (tx, rx) = bounded(4);
+        for _ in 0..3 {
+            tx.send(marker.clone()).unwrap();
+        }
+        drop(rx.recv());
+        drop(tx);
+        assert_eq!(Arc::strong_count(&marker), 3);
+        drop(rx);
+        assert_eq!(Arc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn stress_many_to_many() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+
+        let (tx, rx) = bounded::<usize>(8);
+
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        tx.send(p * PER_PRODUCER + i).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(tx);
+
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || {
+                    let mut got = vec![];
+                    // each producer's values must come out in the order it sent them
+                    let mut last = [None; PRODUCERS];
+                    while let Ok(v) = rx.recv() {
+                        let p = v / PER_PRODUCER;
+                        assert!(last[p] < Some(v));
+                        last[p] = Some(v);
+                        got.push(v);
+                    }
+                    got
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(rx);
+
+        for p in producers {
+            p.join().unwrap();
+        }
+        let mut all = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect::<Vec<_>>();
+        all.sort_unstable();
+        assert_eq!(all, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+    }
+}
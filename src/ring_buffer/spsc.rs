@@ -0,0 +1,451 @@
+/*!
+A lock-free single-producer single-consumer queue over a `RingBuffer`'s storage.
+
+The producer only ever writes `tail` and the consumer only ever writes `head`,
+so each side just needs to see the other's index with acquire/release ordering.
+Both indices count up to `2 * cap` before wrapping, which is how a full buffer
+(`tail - head == cap`) is told apart from an empty one (`tail == head`)
+without giving up a slot.
+*/
+
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::RingBuffer;
+use crate::rawvec::{Allocator, Doubling, Global, GrowthPolicy, RawVec};
+
+/// Keeps the two indices on separate cache lines so the threads don't fight over one.
+#[repr(align(64))]
+pub(super) struct CachePadded<T>(pub(super) T);
+
+struct Shared<T, A: Allocator, G: GrowthPolicy> {
+    buf: RawVec<T, A, G>,
+    cap: usize,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+}
+
+// the slots are handed from one thread to the other, never shared
+unsafe impl<T: Send, A: Allocator + Send + Sync, G: GrowthPolicy + Send + Sync> Send for Shared<T, A, G> {}
+unsafe impl<T: Send, A: Allocator + Send + Sync, G: GrowthPolicy + Send + Sync> Sync for Shared<T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthPolicy> Shared<T, A, G> {
+    fn slot(&self, i: usize) -> *mut T {
+        let i = if i >= self.cap { i - self.cap } else { i };
+        unsafe { self.buf.ptr.as_ptr().add(i) }
+    }
+
+    fn advance(&self, i: usize, n: usize) -> usize {
+        // `i < 2 * cap` and `n <= cap`, so this can't overflow
+        let wrap = 2 * self.cap;
+        if i >= wrap - n {
+            i - (wrap - n)
+        } else {
+            i + n
+        }
+    }
+
+    fn distance(&self, head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + (2 * self.cap - head)
+        }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for Shared<T, A, G> {
+    fn drop(&mut self) {
+        let mut head = *self.head.0.get_mut();
+        let tail = *self.tail.0.get_mut();
+
+        while head != tail {
+            unsafe { ptr::drop_in_place(self.slot(head)) };
+            head = self.advance(head, 1);
+        }
+    }
+}
+
+/// Publishes however many slots were handled, even if a clone or a drop panics mid-batch.
+struct Publish<'a, T, A: Allocator, G: GrowthPolicy> {
+    shared: &'a Shared<T, A, G>,
+    index: &'a AtomicUsize,
+    local: &'a mut usize,
+    start: usize,
+    done: usize,
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for Publish<'_, T, A, G> {
+    fn drop(&mut self) {
+        let next = self.shared.advance(self.start, self.done);
+        *self.local = next;
+        self.index.store(next, Ordering::Release);
+    }
+}
+
+/// The sending half of a queue made by `RingBuffer::split` or `channel`.
+pub struct Producer<T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
+    shared: Arc<Shared<T, A, G>>,
+    tail: usize,
+    // the last head we saw, only refreshed when the queue looks full
+    cached_head: usize,
+}
+
+/// The receiving half of a queue made by `RingBuffer::split` or `channel`.
+pub struct Consumer<T, A: Allocator = Global, G: GrowthPolicy = Doubling> {
+    shared: Arc<Shared<T, A, G>>,
+    head: usize,
+    // the last tail we saw, only refreshed when the queue looks empty
+    cached_tail: usize,
+}
+
+/// Makes an SPSC queue holding up to `cap` elements.
+pub fn channel<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    RingBuffer::with_capacity(cap).split()
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> RingBuffer<T, A, G> {
+    /**
+    Turns the buffer into the two halves of a lock-free SPSC queue over the
+    same storage, keeping its contents. The queue never grows or overwrites,
+    whatever the buffer's `Overflow` policy: a push into a full queue fails.
+    */
+    pub fn split(self) -> (Producer<T, A, G>, Consumer<T, A, G>) {
+        let me = ManuallyDrop::new(self);
+        let head = me.wrapped_index(0);
+        let len = me.len;
+        // `me` is never dropped, so the buffer is moved out exactly once
+        let buf = unsafe { ptr::read(&me.buf) };
+
+        // the indices need room to count up to 2 * cap, so a ZST queue settles for less than usize::MAX
+        let cap = if mem::size_of::<T>() == 0 { usize::MAX / 2 } else { buf.cap() };
+        let tail = head + len;
+
+        let shared = Arc::new(Shared {
+            buf,
+            cap,
+            head: CachePadded(AtomicUsize::new(head)),
+            tail: CachePadded(AtomicUsize::new(tail)),
+        });
+
+        let producer = Producer {
+            shared: shared.clone(),
+            tail,
+            cached_head: head,
+        };
+        let consumer = Consumer {
+            shared,
+            head,
+            cached_tail: tail,
+        };
+
+        (producer, consumer)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Producer<T, A, G> {
+    pub fn capacity(&self) -> usize {
+        self.shared.cap
+    }
+
+    /// A snapshot; the consumer may be popping at the same time.
+    pub fn len(&self) -> usize {
+        let head = self.shared.head.0.load(Ordering::Acquire);
+        self.shared.distance(head, self.tail)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.shared.cap
+    }
+
+    /// How many slots are free, only looking at the consumer's head if the cached one says full.
+    fn free_slots(&mut self, wanted: usize) -> usize {
+        let mut free = self.shared.cap - self.shared.distance(self.cached_head, self.tail);
+        if free < wanted {
+            self.cached_head = self.shared.head.0.load(Ordering::Acquire);
+            free = self.shared.cap - self.shared.distance(self.cached_head, self.tail);
+        }
+        free
+    }
+
+    /// Pushes `val`, handing it back if the queue is full.
+    pub fn push(&mut self, val: T) -> Result<(), T> {
+        if self.free_slots(1) == 0 {
+            return Err(val);
+        }
+
+        unsafe { ptr::write(self.shared.slot(self.tail), val) };
+        self.tail = self.shared.advance(self.tail, 1);
+        self.shared.tail.0.store(self.tail, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Pushes clones of as many elements of `vals` as fit, making them visible
+    /// to the consumer all at once. Returns how many were pushed.
+    pub fn push_slice(&mut self, vals: &[T]) -> usize
+    where
+        T: Clone,
+    {
+        let n = self.free_slots(vals.len()).min(vals.len());
+        if n == 0 {
+            return 0;
+        }
+
+        let shared = &*self.shared;
+        let mut publish = Publish {
+            shared,
+            index: &shared.tail.0,
+            start: self.tail,
+            local: &mut self.tail,
+            done: 0,
+        };
+
+        for v in &vals[..n] {
+            let i = shared.advance(publish.start, publish.done);
+            unsafe { ptr::write(shared.slot(i), v.clone()) };
+            publish.done += 1;
+        }
+
+        n
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Consumer<T, A, G> {
+    pub fn capacity(&self) -> usize {
+        self.shared.cap
+    }
+
+    /// A snapshot; the producer may be pushing at the same time.
+    pub fn len(&self) -> usize {
+        let tail = self.shared.tail.0.load(Ordering::Acquire);
+        self.shared.distance(self.head, tail)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many slots are filled, only looking at the producer's tail if the cached one says empty.
+    fn filled_slots(&mut self, wanted: usize) -> usize {
+        let mut filled = self.shared.distance(self.head, self.cached_tail);
+        if filled < wanted {
+            self.cached_tail = self.shared.tail.0.load(Ordering::Acquire);
+            filled = self.shared.distance(self.head, self.cached_tail);
+        }
+        filled
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.filled_slots(1) == 0 {
+            return None;
+        }
+
+        let val = unsafe { ptr::read(self.shared.slot(self.head)) };
+        self.head = self.shared.advance(self.head, 1);
+        self.shared.head.0.store(self.head, Ordering::Release);
+
+        Some(val)
+    }
+
+    /// Moves as many elements as fit into `out`, dropping what was there,
+    /// and frees their slots all at once. Returns how many were popped.
+    pub fn pop_into(&mut self, out: &mut [T]) -> usize {
+        let n = self.filled_slots(out.len()).min(out.len());
+        if n == 0 {
+            return 0;
+        }
+
+        let shared = &*self.shared;
+        let mut publish = Publish {
+            shared,
+            index: &shared.head.0,
+            start: self.head,
+            local: &mut self.head,
+            done: 0,
+        };
+
+        for slot in &mut out[..n] {
+            let i = shared.advance(publish.start, publish.done);
+            let val = unsafe { ptr::read(shared.slot(i)) };
+            // the slot is ours now, even if dropping the old value panics
+            publish.done += 1;
+            *slot = val;
+        }
+
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn spsc_works() {
+        let (mut p, mut c) = channel::<String>(2);
+        assert_eq!(c.pop(), None);
+        assert_eq!(p.push("Hey".to_string()), Ok(()));
+        assert_eq!(p.push("You".to_string()), Ok(()));
+        assert_eq!(p.push("Guys".to_string()), Err("Guys".to_string()));
+        assert!(p.is_full());
+        assert_eq!(c.len(), 2);
+        assert_eq!(c.pop(), Some("Hey".to_string()));
+        assert_eq!(p.push("Guys".to_string()), Ok(()));
+        assert_eq!(c.pop(), Some("You".to_string()));
+        assert_eq!(c.pop(), Some("Guys".to_string()));
+        assert_eq!(c.pop(), None);
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn split_keeps_contents() {
+        let mut rb = RingBuffer::<u32>::with_capacity(3);
+        rb.extend([1, 2, 3, 4]);
+        let (mut p, mut c) = rb.split();
+        assert_eq!(p.push(5), Err(5));
+        assert_eq!(c.pop(), Some(2));
+        assert_eq!(p.push(5), Ok(()));
+        assert_eq!(c.pop(), Some(3));
+        assert_eq!(c.pop(), Some(4));
+        assert_eq!(c.pop(), Some(5));
+        assert_eq!(c.pop(), None);
+    }
+
+    #[test]
+    fn batches_work() {
+        let (mut p, mut c) = channel::<u32>(5);
+        assert_eq!(p.push_slice(&[1, 2, 3]), 3);
+        assert_eq!(p.push_slice(&[4, 5, 6, 7]), 2);
+        let mut out = [0; 4];
+        assert_eq!(c.pop_into(&mut out), 4);
+        assert_eq!(out, [1, 2, 3, 4]);
+        assert_eq!(p.push_slice(&[6, 7, 8]), 3);
+        assert_eq!(c.pop_into(&mut out), 4);
+        assert_eq!(out, [5, 6, 7, 8]);
+        assert_eq!(c.pop_into(&mut out), 0);
+    }
+
+    #[test]
+    fn panicking_clone_keeps_what_was_pushed() {
+        #[derive(Debug, PartialEq)]
+        struct Fragile(u32);
+
+        impl Clone for Fragile {
+            fn clone(&self) -> Self {
+                assert_ne!(self.0, 3, "boom");
+                Fragile(self.0)
+            }
+        }
+
+        let (mut p, mut c) = channel(4);
+        let vals = [Fragile(1), Fragile(2), Fragile(3)];
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| p.push_slice(&vals)));
+        assert!(res.is_err());
+        assert_eq!(p.len(), 2);
+        assert_eq!(p.push(Fragile(4)), Ok(()));
+        assert_eq!(c.pop(), Some(Fragile(1)));
+        assert_eq!(c.pop(), Some(Fragile(2)));
+        assert_eq!(c.pop(), Some(Fragile(4)));
+        assert_eq!(c.pop(), None);
+    }
+
+    #[test]
+    fn leftovers_are_dropped() {
+        let marker = Arc::new(());
+        let (mut p, c) = channel(4);
+        for _ in 0..3 {
+            p.push(marker.clone()).unwrap();
+        }
+        drop(p);
+        assert_eq!(Arc::strong_count(&marker), 4);
+        drop(c);
+        assert_eq!(Arc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn zst_and_empty_queues_work() {
+        let (mut p, mut c) = channel::<()>(0);
+        for _ in 0..1000 {
+            p.push(()).unwrap();
+        }
+        assert_eq!(c.len(), 1000);
+        assert_eq!(c.pop_into(&mut [(); 600]), 600);
+        assert_eq!(c.len(), 400);
+
+        let (mut p, mut c) = channel::<u8>(0);
+        assert_eq!(p.push(1), Err(1));
+        assert_eq!(c.pop(), None);
+    }
+
+    const STRESS_N: usize = 200_000;
+
+    #[test]
+    fn stress_single_items() {
+        // an odd capacity so the wrap around doesn't line up with anything
+        let (mut p, mut c) = channel::<String>(7);
+
+        let producer = thread::spawn(move || {
+            for i in 0..STRESS_N {
+                let mut v = i.to_string();
+                while let Err(back) = p.push(v) {
+                    v = back;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut expected = 0;
+        while expected < STRESS_N {
+            match c.pop() {
+                Some(v) => {
+                    assert_eq!(v, expected.to_string());
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+
+        producer.join().unwrap();
+        assert_eq!(c.pop(), None);
+    }
+
+    #[test]
+    fn stress_batches() {
+        let (mut p, mut c) = channel::<usize>(64);
+
+        let producer = thread::spawn(move || {
+            let vals = (0..STRESS_N).collect::<Vec<_>>();
+            let mut sent = 0;
+            while sent < STRESS_N {
+                let end = (sent + 13).min(STRESS_N);
+                match p.push_slice(&vals[sent..end]) {
+                    0 => thread::yield_now(),
+                    n => sent += n,
+                }
+            }
+        });
+
+        let mut out = [0; 17];
+        let mut expected = 0;
+        while expected < STRESS_N {
+            let n = c.pop_into(&mut out);
+            if n == 0 {
+                thread::yield_now();
+            }
+            for v in &out[..n] {
+                assert_eq!(*v, expected);
+                expected += 1;
+            }
+        }
+
+        producer.join().unwrap();
+    }
+}
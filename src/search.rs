@@ -4,7 +4,7 @@ Linear search and binary search
 Because of using recursive operations on slices and lack of TCO in Rust,
 the functions operating on slices can stack overflow on large slices
 */
-fn linear_search<T, U, P>(mut it: U, p: P) -> Option<T>
+pub fn linear_search<T, U, P>(mut it: U, p: P) -> Option<T>
 where
     T: PartialEq,
     U: Iterator<Item = T>,
@@ -17,7 +17,7 @@ where
     }
 }
 
-fn linear_search_slice<T: PartialEq>(s: &[T], q: &T) -> Option<usize> {
+pub fn linear_search_slice<T: PartialEq>(s: &[T], q: &T) -> Option<usize> {
     do_linear_search_slice(s, q, 0)
 }
 
@@ -33,7 +33,7 @@ fn do_linear_search_slice<T: PartialEq>(s: &[T], q: &T, i: usize) -> Option<usiz
     }
 }
 
-fn binary_search<T>(s: &[T], q: &T) -> Option<usize>
+pub fn binary_search<T>(s: &[T], q: &T) -> Option<usize>
 where
     T: PartialOrd + PartialEq + std::fmt::Debug,
 {
@@ -59,7 +59,7 @@ where
     }
 }
 
-fn binary_search_rec<T>(s: &[T], q: &T) -> Option<usize>
+pub fn binary_search_rec<T>(s: &[T], q: &T) -> Option<usize>
 where
     T: PartialOrd + PartialEq,
 {
@@ -70,7 +70,7 @@ where
     } else if q < &s[mid] && s.len() > 1 {
         binary_search_rec(&s[..mid], q)
     } else if q > &s[mid] && s.len() > 1 && (mid + 1) < s.len() {
-        binary_search_rec(&s[mid + 1..], q).and_then(|i| Some(i + mid + 1))
+        binary_search_rec(&s[mid + 1..], q).map(|i| i + mid + 1)
     } else {
         None
     }
@@ -106,6 +106,19 @@ mod tests {
         assert_eq!(None, binary_search(s, &23));
     }
 
+    #[test]
+    fn search_works_on_array_list() {
+        use crate::array_list::ArrayList;
+
+        let mut l = ArrayList::new();
+        l.extend_from_slice(&[1, 3, 5, 8, 13, 21]);
+        assert_eq!(Some(3), linear_search_slice(&l, &8));
+        assert_eq!(Some(4), binary_search(&l, &13));
+        assert_eq!(Some(5), binary_search_rec(&l, &21));
+        assert_eq!(None, binary_search(&l[..3], &8));
+        assert_eq!(Some(13), linear_search(l.iter().copied(), |&n| n > 8));
+    }
+
     #[test]
     fn binary_search_rec_works() {
         let s = [1, 3, 5, 8, 13, 21].as_slice();
@@ -1,87 +1,196 @@
 #![allow(dead_code)]
 
+use std::iter;
+use std::slice;
+
 use crate::array_list::ArrayList;
-use crate::linked_list::LinkedList;
+use crate::linked_list::{self, LinkedList};
+use crate::traits;
+
+traits::adapter! {
+    /**
+    A collection a `Stack` can keep its elements in. `push`, `pop` and `peek` all
+    work on the same end of it.
+    */
+    backend StackBackend;
+    /// A stack on top of any `StackBackend`. The aliases name the ones the crate ships with.
+    adapter GenericStack: Stack;
+    /// `for_each_stack!(m!(args..))` is `m!(args.., [ArrayStack, ..])` for every alias
+    /// below, so the tests and benches that use it cover each one.
+    aliases for_each_stack {
+        ArrayStack<T> = ArrayList<T>,
+        LinkedStack<T> = LinkedList<T>,
+        StdArrayStack<T> = Vec<T>,
+        StdLinkedStack<T> = std::collections::LinkedList<T>,
+    }
+}
 
 /* implementations based on crate's collections */
 
-pub struct ArrayStack<T> {
-    list: ArrayList<T>
-}
+impl<T> StackBackend for ArrayList<T> {
+    type Item = T;
+    type Iter<'a> = iter::Rev<slice::Iter<'a, T>>
+    where
+        T: 'a;
 
-impl<T> ArrayStack<T> {
-    pub fn new() -> Self {
-        ArrayStack { list: ArrayList::<T>::new() }
+    fn with_capacity(cap: usize) -> Self {
+        ArrayList::with_capacity(cap)
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push(val)
+    fn push(&mut self, val: T) {
+        ArrayList::push(self, val)
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop()
+    fn pop(&mut self) -> Option<T> {
+        ArrayList::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.last()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.last_mut()
     }
-}
 
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self).rev()
+    }
+
+    fn len(&self) -> usize {
+        ArrayList::len(self)
+    }
 
-pub struct LinkedStack<T> {
-    list: LinkedList<T>
+    fn clear(&mut self) {
+        ArrayList::clear(self)
+    }
 }
 
-impl<T> LinkedStack<T> {
-    pub fn new() -> Self {
-        LinkedStack { list: LinkedList::<T>::new() }
+impl<T> StackBackend for LinkedList<T> {
+    type Item = T;
+    type Iter<'a> = linked_list::Iter<'a, T>
+    where
+        T: 'a;
+
+    // nodes are allocated one at a time, so there's nothing to reserve
+    fn with_capacity(_cap: usize) -> Self {
+        LinkedList::new()
+    }
+
+    fn push(&mut self, val: T) {
+        self.push_front(val)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.front_mut()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        LinkedList::iter(self)
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push_front(val)
+    fn len(&self) -> usize {
+        LinkedList::len(self)
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop_front()
+    fn clear(&mut self) {
+        *self = LinkedList::new()
     }
 }
 
 /* implementations based on stdlib collections */
 
-pub struct StdArrayStack<T> {
-    list: Vec<T>
-}
+impl<T> StackBackend for Vec<T> {
+    type Item = T;
+    type Iter<'a> = iter::Rev<slice::Iter<'a, T>>
+    where
+        T: 'a;
 
-impl<T> StdArrayStack<T> {
-    pub fn new() -> Self {
-        StdArrayStack { list: Vec::<T>::new() }
+    fn with_capacity(cap: usize) -> Self {
+        Vec::with_capacity(cap)
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push(val)
+    fn push(&mut self, val: T) {
+        Vec::push(self, val)
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop()
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.last()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.last_mut()
     }
-}
 
-pub struct StdLinkedStack<T> {
-    list: std::collections::LinkedList<T>
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self).rev()
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
 }
 
-impl<T> StdLinkedStack<T> {
-    pub fn new() -> Self {
-        StdLinkedStack { list: std::collections::LinkedList::<T>::new() }
+impl<T> StackBackend for std::collections::LinkedList<T> {
+    type Item = T;
+    type Iter<'a> = std::collections::linked_list::Iter<'a, T>
+    where
+        T: 'a;
+
+    // nodes are allocated one at a time, so there's nothing to reserve
+    fn with_capacity(_cap: usize) -> Self {
+        std::collections::LinkedList::new()
     }
 
-    pub fn push(&mut self, val: T) {
-        self.list.push_front(val)
+    fn push(&mut self, val: T) {
+        self.push_front(val)
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.list.pop_front()
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.front_mut()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        std::collections::LinkedList::iter(self)
+    }
+
+    fn len(&self) -> usize {
+        std::collections::LinkedList::len(self)
+    }
+
+    fn clear(&mut self) {
+        std::collections::LinkedList::clear(self)
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
 
     #[test]
     fn array_stack_works() {
@@ -122,4 +231,6 @@ mod tests {
         assert_eq!(s.pop(), Some(1));
         assert_eq!(s.pop(), Some(3478));
     }
+
+    for_each_stack!(test_util::adapter_tests!(Stack, pops: [3, 2, 1]));
 }
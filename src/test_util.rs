@@ -0,0 +1,79 @@
+/*!
+Helpers shared by the unit tests.
+*/
+
+/// A xorshift generator, so the randomized tests are reproducible without extra crates.
+pub(crate) fn rng(mut state: u64) -> impl FnMut() -> usize {
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize
+    }
+}
+
+/**
+Checks every adapter through its `traits` interface and its own inherent
+methods, given the order that pushing `1, 2, 3` pops them back out in. The
+adapters come from `for_each_stack!` or `for_each_queue!`.
+*/
+macro_rules! adapter_tests {
+    ($iface:ident, pops: $order:expr, [$($adapter:ident),*]) => {
+        fn works_through_the_trait<S: $crate::traits::$iface<usize> + Default>() {
+            let order: [usize; 3] = $order;
+            let mut s = S::default();
+            assert!(s.is_empty());
+            assert_eq!(s.peek(), None);
+            s.push(1);
+            s.push(2);
+            s.push(3);
+            assert_eq!(s.len(), 3);
+            assert_eq!(s.peek(), Some(&order[0]));
+
+            let mut out = vec![];
+            while let Some(v) = s.pop() {
+                out.push(v);
+            }
+            assert_eq!(out, order);
+
+            s.push(4);
+            s.clear();
+            assert!(s.is_empty());
+            assert_eq!(s.pop(), None);
+        }
+
+        #[test]
+        fn every_backend_works_through_the_trait() {
+            $(works_through_the_trait::<$adapter<usize>>();)*
+        }
+
+        // `peek_mut`, `iter` and `drain` aren't on the traits, so this expands once per adapter
+        #[test]
+        fn every_backend_peeks_iterates_and_drains() {
+            let order: [usize; 3] = $order;
+            $({
+                // more than the capacity, so the preallocated backends have to grow
+                let mut s = $adapter::<usize>::with_capacity(2);
+                s.extend([1, 2, 3]);
+                *s.peek_mut().unwrap() += 10;
+                assert_eq!(s.peek(), Some(&(order[0] + 10)));
+                let mut expected = order.to_vec();
+                expected[0] += 10;
+                assert_eq!(s.iter().copied().collect::<Vec<_>>(), expected);
+                assert_eq!((&s).into_iter().len(), 3);
+
+                let mut drain = s.drain();
+                assert_eq!(drain.len(), 3);
+                assert_eq!(drain.next(), Some(expected[0]));
+                drop(drain);
+                assert!(s.is_empty());
+                assert_eq!(s.peek_mut(), None);
+
+                s.push(4);
+                assert_eq!(s.drain().collect::<Vec<_>>(), vec![4]);
+            })*
+        }
+    };
+}
+
+pub(crate) use adapter_tests;
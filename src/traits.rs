@@ -0,0 +1,267 @@
+/*!
+The interfaces every stack and queue in the crate share, so code can be
+written once and run against any backend.
+*/
+
+/// A last-in, first-out collection.
+pub trait Stack<T> {
+    fn push(&mut self, val: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    /// The element `pop` would return next.
+    fn peek(&self) -> Option<&T>;
+
+    fn peek_mut(&mut self) -> Option<&mut T>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn clear(&mut self);
+}
+
+/// A first-in, first-out collection.
+pub trait Queue<T> {
+    fn push(&mut self, val: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    /// The element `pop` would return next.
+    fn peek(&self) -> Option<&T>;
+
+    fn peek_mut(&mut self) -> Option<&mut T>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn clear(&mut self);
+}
+
+/**
+Defines a backend trait, an adapter struct generic over it, and everything the
+adapter gets from its backend: the inherent methods, the `traits` interface,
+`Extend`, `FromIterator`, the `From` conversions, `IntoIterator` for `&` and a
+`Drain`. `stack` and `queue` only differ in which ends their backends work on.
+
+It also defines the aliases for the backends the crate ships with, and an
+exported macro that hands their names to another macro, so the tests and
+benches that go through it pick up a new alias without being told about it.
+*/
+macro_rules! adapter {
+    (
+        $(#[$backend_attr:meta])*
+        backend $backend:ident;
+        $(#[$adapter_attr:meta])*
+        adapter $adapter:ident: $iface:ident;
+        $(#[$each_attr:meta])*
+        aliases $each:ident {
+            $($alias:ident<$t:ident> = $aliased:ty),* $(,)?
+        }
+    ) => {
+        $(pub type $alias<$t> = $adapter<$aliased>;)*
+
+        $crate::traits::adapter!(@each ($) $(#[$each_attr])* $each [$($alias),*]);
+
+        $(#[$backend_attr])*
+        pub trait $backend {
+            type Item;
+
+            /// Walks the elements in the order `pop` would return them.
+            type Iter<'a>: Iterator<Item = &'a Self::Item> + ExactSizeIterator
+            where
+                Self: 'a;
+
+            /// An empty backend with room for `cap` elements, if it preallocates at all.
+            /// The adapter's `new` and `default` use it too, with a `cap` of 0.
+            fn with_capacity(cap: usize) -> Self;
+
+            fn push(&mut self, val: Self::Item);
+
+            fn pop(&mut self) -> Option<Self::Item>;
+
+            fn peek(&self) -> Option<&Self::Item>;
+
+            fn peek_mut(&mut self) -> Option<&mut Self::Item>;
+
+            fn iter(&self) -> Self::Iter<'_>;
+
+            fn len(&self) -> usize;
+
+            fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            fn clear(&mut self);
+        }
+
+        $(#[$adapter_attr])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $adapter<B> {
+            list: B,
+        }
+
+        impl<B: $backend> $adapter<B> {
+            pub fn new() -> Self {
+                Self::with_capacity(0)
+            }
+
+            /// An empty one with room for `cap` elements, for the backends that preallocate.
+            pub fn with_capacity(cap: usize) -> Self {
+                $adapter { list: B::with_capacity(cap) }
+            }
+
+            pub fn push(&mut self, val: B::Item) {
+                self.list.push(val)
+            }
+
+            pub fn pop(&mut self) -> Option<B::Item> {
+                self.list.pop()
+            }
+
+            /// The element `pop` would return next.
+            pub fn peek(&self) -> Option<&B::Item> {
+                self.list.peek()
+            }
+
+            pub fn peek_mut(&mut self) -> Option<&mut B::Item> {
+                self.list.peek_mut()
+            }
+
+            /// Walks the elements in the order `pop` would return them.
+            pub fn iter(&self) -> B::Iter<'_> {
+                self.list.iter()
+            }
+
+            /// Pops the elements as it's iterated, dropping any that are left when it is.
+            pub fn drain(&mut self) -> Drain<'_, B> {
+                Drain { inner: self }
+            }
+
+            pub fn len(&self) -> usize {
+                self.list.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.list.is_empty()
+            }
+
+            pub fn clear(&mut self) {
+                self.list.clear()
+            }
+        }
+
+        impl<B: $backend> Default for $adapter<B> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<B: $backend> $crate::traits::$iface<B::Item> for $adapter<B> {
+            fn push(&mut self, val: B::Item) {
+                self.push(val)
+            }
+
+            fn pop(&mut self) -> Option<B::Item> {
+                self.pop()
+            }
+
+            fn peek(&self) -> Option<&B::Item> {
+                self.peek()
+            }
+
+            fn peek_mut(&mut self) -> Option<&mut B::Item> {
+                self.peek_mut()
+            }
+
+            fn len(&self) -> usize {
+                self.len()
+            }
+
+            fn clear(&mut self) {
+                self.clear()
+            }
+        }
+
+        /// `Extend`, `FromIterator` and the `From` conversions, all in terms of `push`.
+        impl<B: $backend> Extend<B::Item> for $adapter<B> {
+            fn extend<I: IntoIterator<Item = B::Item>>(&mut self, iter: I) {
+                for v in iter {
+                    self.push(v);
+                }
+            }
+        }
+
+        impl<B: $backend> FromIterator<B::Item> for $adapter<B> {
+            fn from_iter<I: IntoIterator<Item = B::Item>>(iter: I) -> Self {
+                let mut s = Self::new();
+                s.extend(iter);
+                s
+            }
+        }
+
+        impl<B: $backend> From<Vec<B::Item>> for $adapter<B> {
+            fn from(v: Vec<B::Item>) -> Self {
+                v.into_iter().collect()
+            }
+        }
+
+        impl<B: $backend, const N: usize> From<[B::Item; N]> for $adapter<B> {
+            fn from(arr: [B::Item; N]) -> Self {
+                arr.into_iter().collect()
+            }
+        }
+
+        impl<'a, B: $backend> IntoIterator for &'a $adapter<B> {
+            type Item = &'a B::Item;
+            type IntoIter = B::Iter<'a>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        #[doc = concat!("Returned by `", stringify!($adapter), "::drain`.")]
+        pub struct Drain<'a, B: $backend> {
+            inner: &'a mut $adapter<B>,
+        }
+
+        impl<B: $backend> Iterator for Drain<'_, B> {
+            type Item = B::Item;
+
+            fn next(&mut self) -> Option<B::Item> {
+                self.inner.pop()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.inner.len(), Some(self.inner.len()))
+            }
+        }
+
+        impl<B: $backend> ExactSizeIterator for Drain<'_, B> {}
+
+        impl<B: $backend> Drop for Drain<'_, B> {
+            fn drop(&mut self) {
+                self.inner.clear();
+            }
+        }
+    };
+
+    // `$d` is a `$`, which a macro can't otherwise write into one it defines
+    (@each ($d:tt) $(#[$attr:meta])* $each:ident [$($alias:ident),*]) => {
+        $(#[$attr])*
+        #[macro_export]
+        macro_rules! $each {
+            ($d($d m:ident)::+ ! ($d($d args:tt)*)) => {
+                $d($d m)::+! { $d($d args)*, [$($alias),*] }
+            };
+        }
+    };
+}
+
+pub(crate) use adapter;
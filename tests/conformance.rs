@@ -0,0 +1,249 @@
+//! Checks that every collection's std trait impls behave like its std counterpart.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use rust_dsa::array_list::ArrayList;
+use rust_dsa::doubly_linked_list::DoublyLinkedList;
+use rust_dsa::linked_list::LinkedList;
+use rust_dsa::queue::{ArrayQueue, LinkedQueue, StdArrayQueue, StdLinkedQueue};
+use rust_dsa::ring_buffer::RingBuffer;
+use rust_dsa::stack::{ArrayStack, LinkedStack, StdArrayStack, StdLinkedStack};
+
+fn samples() -> Vec<Vec<i32>> {
+    vec![
+        vec![],
+        vec![1],
+        vec![2],
+        vec![1, 2],
+        vec![1, 2, 3],
+        vec![1, 2, 4],
+        vec![3, 2, 1],
+        vec![5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    ]
+}
+
+fn hash_of<T: Hash>(v: &T) -> u64 {
+    let mut h = DefaultHasher::new();
+    v.hash(&mut h);
+    h.finish()
+}
+
+/// For sequence collections with `iter()`, compared against a std collection.
+macro_rules! sequence_conformance {
+    ($name:ident, $ty:ident, $std:ty, $new:expr) => {
+        mod $name {
+            use super::*;
+
+            fn ours(v: &[i32]) -> $ty<i32> {
+                $ty::from(v.to_vec())
+            }
+
+            fn theirs(v: &[i32]) -> $std {
+                v.iter().copied().collect()
+            }
+
+            fn items(c: &$ty<i32>) -> Vec<i32> {
+                c.iter().copied().collect()
+            }
+
+            #[test]
+            fn conversions_keep_order() {
+                for v in samples() {
+                    assert_eq!(items(&ours(&v)), v);
+                    assert_eq!(items(&v.iter().copied().collect::<$ty<_>>()), v);
+                }
+                assert_eq!(items(&$ty::from([4, 5, 6])), vec![4, 5, 6]);
+            }
+
+            #[test]
+            fn pushing_after_a_conversion_matches_std() {
+                for v in samples() {
+                    let mut t = theirs(&v);
+                    t.extend([7]);
+                    let expected = t.into_iter().collect::<Vec<_>>();
+
+                    let mut o = ours(&v);
+                    o.extend([7]);
+                    assert_eq!(items(&o), expected);
+                    let mut o = v.iter().copied().collect::<$ty<_>>();
+                    o.extend([7]);
+                    assert_eq!(items(&o), expected);
+                }
+
+                let mut o = $ty::from([4, 5, 6]);
+                o.extend([7]);
+                assert_eq!(items(&o), vec![4, 5, 6, 7]);
+            }
+
+            #[test]
+            fn default_is_empty() {
+                let c = $ty::<i32>::default();
+                assert_eq!(c.len(), 0);
+                assert!(c.is_empty());
+                assert_eq!(c.iter().next(), None);
+            }
+
+            #[test]
+            fn clone_is_deep() {
+                for v in samples() {
+                    let original = ours(&v);
+                    let mut copy = original.clone();
+                    assert_eq!(copy, original);
+                    if let Some(first) = copy.iter_mut().next() {
+                        *first += 100;
+                        assert_ne!(copy, original);
+                    }
+                    assert_eq!(items(&original), v);
+                }
+
+                let strings = $ty::from(vec!["a".to_string(), "b".to_string()]);
+                assert_eq!(strings.clone(), strings);
+            }
+
+            #[test]
+            fn debug_matches_std() {
+                for v in samples() {
+                    assert_eq!(format!("{:?}", ours(&v)), format!("{:?}", theirs(&v)));
+                }
+            }
+
+            #[test]
+            fn comparisons_match_std() {
+                for a in samples() {
+                    for b in samples() {
+                        let (oa, ob) = (ours(&a), ours(&b));
+                        let (ta, tb) = (theirs(&a), theirs(&b));
+                        assert_eq!(oa == ob, ta == tb, "{a:?} == {b:?}");
+                        assert_eq!(oa.partial_cmp(&ob), ta.partial_cmp(&tb), "{a:?} <> {b:?}");
+                        assert_eq!(oa.cmp(&ob), ta.cmp(&tb), "{a:?} <> {b:?}");
+                        if oa == ob {
+                            assert_eq!(hash_of(&oa), hash_of(&ob));
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn extend_matches_std() {
+                let mut o: $ty<i32> = $new;
+                let mut t = <$std>::new();
+                for v in samples() {
+                    o.extend(v.iter().copied());
+                    t.extend(v.iter().copied());
+                    o.extend(&v);
+                    t.extend(&v);
+                }
+                assert_eq!(items(&o), t.into_iter().collect::<Vec<_>>());
+            }
+        }
+    };
+}
+
+sequence_conformance!(array_list, ArrayList, Vec<i32>, ArrayList::new());
+sequence_conformance!(
+    ring_buffer,
+    RingBuffer,
+    std::collections::VecDeque<i32>,
+    RingBuffer::default()
+);
+sequence_conformance!(
+    linked_list,
+    LinkedList,
+    std::collections::LinkedList<i32>,
+    LinkedList::new()
+);
+sequence_conformance!(
+    doubly_linked_list,
+    DoublyLinkedList,
+    std::collections::LinkedList<i32>,
+    DoublyLinkedList::new()
+);
+
+/// For the stacks and queues, which only have `push`/`pop`. `$model` is the
+/// order `pop` hands elements back in, given the order they were pushed.
+macro_rules! adapter_conformance {
+    ($name:ident, $ty:ident, $model:expr) => {
+        mod $name {
+            use super::*;
+
+            fn drain(mut c: $ty<i32>) -> Vec<i32> {
+                let mut out = vec![];
+                while let Some(v) = c.pop() {
+                    out.push(v);
+                }
+                out
+            }
+
+            fn expected(v: &[i32]) -> Vec<i32> {
+                let model: fn(&[i32]) -> Vec<i32> = $model;
+                model(v)
+            }
+
+            #[test]
+            fn conversions_keep_push_order() {
+                for v in samples() {
+                    assert_eq!(drain($ty::from(v.clone())), expected(&v));
+                    assert_eq!(drain(v.iter().copied().collect()), expected(&v));
+
+                    let mut c = $ty::default();
+                    c.extend(v.iter().copied());
+                    assert_eq!(drain(c), expected(&v));
+                }
+                assert_eq!(drain($ty::from([1, 2, 3])), expected(&[1, 2, 3]));
+            }
+
+            #[test]
+            fn default_is_empty() {
+                assert_eq!($ty::<i32>::default().pop(), None);
+            }
+
+            #[test]
+            fn clone_is_deep() {
+                for v in samples() {
+                    let original = $ty::from(v.clone());
+                    let mut copy = original.clone();
+                    assert_eq!(copy, original);
+                    assert_eq!(format!("{:?}", copy), format!("{:?}", original));
+                    if copy.pop().is_some() {
+                        assert_ne!(copy, original);
+                    }
+                    assert_eq!(drain(original), expected(&v));
+                }
+            }
+
+            #[test]
+            fn comparisons_are_consistent() {
+                for a in samples() {
+                    for b in samples() {
+                        let (ca, cb) = ($ty::from(a.clone()), $ty::from(b.clone()));
+                        assert_eq!(ca == cb, a == b);
+                        assert_eq!(ca.cmp(&cb).is_eq(), a == b);
+                        assert_eq!(ca.partial_cmp(&cb), Some(ca.cmp(&cb)));
+                        assert_eq!(ca.cmp(&cb), cb.cmp(&ca).reverse());
+                        if ca == cb {
+                            assert_eq!(hash_of(&ca), hash_of(&cb));
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
+
+fn lifo(v: &[i32]) -> Vec<i32> {
+    v.iter().rev().copied().collect()
+}
+
+fn fifo(v: &[i32]) -> Vec<i32> {
+    v.to_vec()
+}
+
+adapter_conformance!(array_stack, ArrayStack, lifo);
+adapter_conformance!(linked_stack, LinkedStack, lifo);
+adapter_conformance!(std_array_stack, StdArrayStack, lifo);
+adapter_conformance!(std_linked_stack, StdLinkedStack, lifo);
+adapter_conformance!(array_queue, ArrayQueue, fifo);
+adapter_conformance!(linked_queue, LinkedQueue, fifo);
+adapter_conformance!(std_array_queue, StdArrayQueue, fifo);
+adapter_conformance!(std_linked_queue, StdLinkedQueue, fifo);